@@ -0,0 +1,818 @@
+mod repl;
+
+use std::{
+    io::{BufRead, BufReader, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    sync::Mutex,
+};
+
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use texas_odds::{
+    cache::{CanonicalKey, OddsCache},
+    chart::preflop_chart,
+    odds::{get_best_hand, showdown_equity, CancelToken, Odds, Stage, WinRate, DEFAULT_ITERATIONS},
+    outs::outs_against,
+    range::{equity_vs_range, ParseRangeError, Range},
+    scenario::{Scenario, ScenarioError},
+    texas::{Card, HandType, ParseCardError},
+};
+
+#[derive(Parser)]
+/// Texas Hold'em odds calculator
+///
+/// Without a subcommand, computes the odds of your hand like `texas-odds odds`.
+#[command(
+    author,
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(flatten)]
+    odds: OddsArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Args)]
+struct OddsArgs {
+    /// Your cards no.1  -- 手牌 1
+    #[arg(required = true, value_parser = parse_card)]
+    hole_cards_0: Option<Card>,
+    /// Your cards no.2  -- 手牌 2
+    #[arg(required = true, value_parser = parse_card)]
+    hole_cards_1: Option<Card>,
+    /// The community cards -- 公开池
+    ///
+    /// It should be empty or at least 3 cards.
+    ///
+    /// Input format:
+    ///
+    /// >  h2 d3 s4 c5 d13
+    ///
+    /// - hole_cards = [♥️2, ♦️3]
+    ///
+    /// - community_cards = [♠️4, ♣️5, ♦️K]
+    ///
+    /// h = hearts 红心 ♥️ , d = diamonds 方块 ♦️, s = spades 黑桃 ♠️, c = clubs 梅花 ♣️
+    #[arg(value_parser = parse_card)]
+    community_cards: Vec<Card>,
+
+    /// The number of players (default = 2)
+    #[arg(short, long, value_name = "N_PLAYERS")]
+    n_players: Option<usize>,
+
+    /// Output format -- 输出格式
+    #[arg(short, long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    /// Keep the computed odds in this file and reuse them in later runs -- 缓存文件
+    #[arg(long, value_name = "FILE")]
+    cache: Option<PathBuf>,
+
+    /// Print the distribution of the win rate against one random hand instead -- 胜率分布
+    #[arg(long, conflicts_with_all = ["n_players", "cache"])]
+    win_rate: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Odds of your hand against random hands -- 胜率
+    ///
+    /// >  texas-odds odds h2 d3 s4 c5 d13 -n 3
+    Odds(OddsArgs),
+    /// Describe the best hand of 5 to 7 cards -- 牌型
+    ///
+    /// >  texas-odds eval hA dA cA sK hK
+    Eval {
+        /// 5 to 7 cards -- 5 到 7 张牌
+        #[arg(required = true, num_args = 5..=7, value_parser = parse_card)]
+        cards: Vec<Card>,
+    },
+    /// Exact equity of known hands head to head -- 已知手牌对比
+    ///
+    /// Each hand is two cards joined by a comma:
+    ///
+    /// >  texas-odds compare hA,dA cK,sK --board h2 d7 sQ
+    Compare {
+        /// The hands, at least two -- 手牌, 至少两手
+        #[arg(required = true, num_args = 2.., value_parser = parse_hand)]
+        hands: Vec<[Card; 2]>,
+        /// The community cards -- 公开池
+        #[arg(short, long, num_args = 0..=5, value_parser = parse_card)]
+        board: Vec<Card>,
+    },
+    /// Equity against a range of hands -- 对抗手牌范围
+    ///
+    /// Ranges are written rank first: pairs `TT+`, `22-55`, suited `AKs`, `ATs+`, `K9s-KJs`,
+    /// offsuit `AQo`, both `AK`, or exact hands `AhKh`, separated by commas.
+    ///
+    /// >  texas-odds range hA dK "TT+, AQs+, AKo" h2 d7 sQ
+    Range {
+        /// Your cards no.1  -- 手牌 1
+        #[arg(value_parser = parse_card)]
+        hole_cards_0: Card,
+        /// Your cards no.2  -- 手牌 2
+        #[arg(value_parser = parse_card)]
+        hole_cards_1: Card,
+        /// The opponent range -- 对手范围
+        range: String,
+        /// The community cards -- 公开池
+        #[arg(value_parser = parse_card)]
+        community_cards: Vec<Card>,
+        /// Number of simulated runouts -- 模拟次数
+        #[arg(short, long, default_value_t = DEFAULT_ITERATIONS)]
+        iterations: usize,
+        /// Random seed, the same seed gives the same result -- 随机种子
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Cards of the next street that improve your hand -- 补牌
+    ///
+    /// >  texas-odds outs hA hK h2 h7 s9
+    ///
+    /// With `--vs`, the cards that put you ahead of a known hand:
+    ///
+    /// >  texas-odds outs hA dK h2 s7 c9 --vs hQ,dQ
+    Outs {
+        /// Your cards no.1  -- 手牌 1
+        #[arg(value_parser = parse_card)]
+        hole_cards_0: Card,
+        /// Your cards no.2  -- 手牌 2
+        #[arg(value_parser = parse_card)]
+        hole_cards_1: Card,
+        /// The flop or the turn -- 翻牌或转牌
+        #[arg(required = true, num_args = 3..=4, value_parser = parse_card)]
+        community_cards: Vec<Card>,
+        /// The opponent hand, two cards joined by a comma -- 对手手牌
+        #[arg(long, value_parser = parse_hand)]
+        vs: Option<[Card; 2]>,
+    },
+    /// The 13x13 preflop starting hand chart -- 起手牌胜率表
+    ///
+    /// Pairs on the diagonal, suited hands above it and offsuit hands below,
+    /// with the equity against `n - 1` random hands.
+    ///
+    /// >  texas-odds chart -n 6 --format svg --output chart.svg
+    Chart {
+        /// The number of players -- 玩家数
+        #[arg(short, long, value_name = "N_PLAYERS", default_value_t = 2)]
+        n_players: usize,
+        /// Output format -- 输出格式
+        #[arg(short, long, value_enum, default_value_t = ChartFormat::Table)]
+        format: ChartFormat,
+        /// Write to this file instead of stdout -- 输出文件
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Evaluate many scenarios, one per line -- 批量计算
+    ///
+    /// Each line is either the positional arguments with optional `n=` and `dead=` fields:
+    ///
+    /// >  h2 d3 s4 c5 d13 n=3 dead=hA,sK
+    ///
+    /// or a JSON object:
+    ///
+    /// >  {"hole_cards": ["h2", "d3"], "community_cards": ["s4", "c5", "d13"], "n_players": 3, "dead_cards": ["hA", "sK"]}
+    ///
+    /// Empty lines and lines starting with `#` are skipped.
+    /// Invalid lines produce an error result instead of stopping the batch.
+    Batch {
+        /// The scenario file, stdin if omitted or `-`
+        input: Option<PathBuf>,
+
+        /// Number of scenarios evaluated in parallel -- 并行数
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+
+        /// Output format -- 输出格式
+        #[arg(short, long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+
+        /// Keep the computed odds in this file and reuse them in later runs -- 缓存文件
+        #[arg(long, value_name = "FILE")]
+        cache: Option<PathBuf>,
+    },
+    /// Interactive mode: set the hand, deal street by street and see the odds after each change -- 交互模式
+    ///
+    /// Commands: hand, flop, turn, river, board, players, undo, reset, show, help, quit
+    Repl,
+}
+
+fn parse_card(s: &str) -> Result<Card, ParseCardError> {
+    s.parse()
+}
+
+/// Two cards joined by a comma, e.g. `hA,dA`
+fn parse_hand(s: &str) -> Result<[Card; 2], String> {
+    let cards = s
+        .split(',')
+        .map(|x| x.trim().parse::<Card>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    cards
+        .try_into()
+        .map_err(|_| format!("Expected two cards joined by a comma, got {s}"))
+}
+
+/// Exits with a usage error if a card is used twice
+fn check_distinct<'a>(cards: impl IntoIterator<Item = &'a Card>) {
+    let mut seen: Vec<&Card> = Vec::new();
+    for card in cards {
+        if seen.contains(&card) {
+            Cli::command()
+                .error(ErrorKind::ValueValidation, format!("{card} is used twice"))
+                .exit();
+        }
+        seen.push(card);
+    }
+}
+
+fn hand_type_zh(hand: HandType) -> &'static str {
+    match hand {
+        HandType::HighCard => "高牌",
+        HandType::Pair => "一对",
+        HandType::TwoPair => "两对",
+        HandType::ThreeOfAKind => "三条",
+        HandType::Straight => "顺子",
+        HandType::Flush => "同花",
+        HandType::FullHouse => "葫芦",
+        HandType::FourOfAKind => "四条",
+        HandType::StraightFlush => "同花顺",
+        HandType::RoyalFlush => "皇家同花顺",
+    }
+}
+
+fn print_cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Human readable text
+    Table,
+    /// One JSON object
+    Json,
+    /// A header line and one row, with a column for every hand type
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ChartFormat {
+    /// A colored grid
+    Table,
+    /// `hand,equity` rows
+    Csv,
+    /// An SVG image
+    Svg,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    n_players: usize,
+    hole_cards: [Card; 2],
+    community_cards: &'a [Card],
+    odds: &'a Odds,
+}
+
+#[derive(Serialize)]
+struct BatchReport<'a> {
+    line: usize,
+    #[serde(flatten)]
+    report: Report<'a>,
+    dead_cards: &'a [Card],
+}
+
+#[derive(Serialize)]
+struct BatchError<'a> {
+    line: usize,
+    error: &'a str,
+}
+
+#[derive(Serialize)]
+struct WinRateReport<'a> {
+    hole_cards: [Card; 2],
+    community_cards: &'a [Card],
+    win_rate: &'a WinRate,
+}
+
+/// The statistics of `win_rate_csv_row`, then our and the opponent's rate of every hand type
+fn win_rate_csv_header() -> Vec<String> {
+    let mut header: Vec<String> = [
+        "mean",
+        "mean_tie_rate",
+        "min",
+        "percentile25",
+        "median",
+        "percentile75",
+        "max",
+        "std",
+    ]
+    .map(String::from)
+    .to_vec();
+    header.extend(HandType::ALL.iter().map(|x| format!("self_{x}")));
+    header.extend(HandType::ALL.iter().map(|x| format!("other_{x}")));
+    header
+}
+
+fn win_rate_csv_row(win_rate: &WinRate) -> Vec<String> {
+    let mut row: Vec<String> = [
+        win_rate.mean,
+        win_rate.mean_tie_rate,
+        win_rate.min,
+        win_rate.percentile25,
+        win_rate.median,
+        win_rate.percentile75,
+        win_rate.max,
+        win_rate.std,
+    ]
+    .map(|x| x.to_string())
+    .to_vec();
+    for rates in [&win_rate.self_rate, &win_rate.other_rate] {
+        row.extend(
+            HandType::ALL
+                .iter()
+                .map(|x| rates.get(x).unwrap_or(&0.).to_string()),
+        );
+    }
+    row
+}
+
+fn csv_header() -> Vec<String> {
+    let mut header = vec!["n_players".to_string(), "win".into(), "tie".into()];
+    header.extend(HandType::ALL.iter().map(|x| x.to_string()));
+    header
+}
+
+fn csv_row(n_players: usize, odds: &Odds) -> Vec<String> {
+    let mut row = vec![
+        n_players.to_string(),
+        odds.win.to_string(),
+        odds.tie.to_string(),
+    ];
+    row.extend(
+        HandType::ALL
+            .iter()
+            .map(|x| odds.hand_rate.get(x).unwrap_or(&0.).to_string()),
+    );
+    row
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match cli.command {
+        None => odds(cli.odds),
+        Some(Command::Odds(args)) => odds(args),
+        Some(Command::Eval { cards }) => eval(&cards),
+        Some(Command::Compare { hands, board }) => compare(&hands, &board),
+        Some(Command::Range {
+            hole_cards_0,
+            hole_cards_1,
+            range: spec,
+            community_cards,
+            iterations,
+            seed,
+        }) => range(
+            [hole_cards_0, hole_cards_1],
+            &spec,
+            &community_cards,
+            iterations,
+            seed,
+        ),
+        Some(Command::Outs {
+            hole_cards_0,
+            hole_cards_1,
+            community_cards,
+            vs,
+        }) => outs([hole_cards_0, hole_cards_1], &community_cards, vs),
+        Some(Command::Chart {
+            n_players,
+            format,
+            output,
+        }) => chart(n_players, format, output),
+        Some(Command::Batch {
+            input,
+            jobs,
+            format,
+            cache,
+        }) => batch(input, jobs, format, cache),
+        Some(Command::Repl) => {
+            repl::run(std::io::stdin().lock(), std::io::stdout().lock()).unwrap()
+        }
+    }
+}
+
+fn odds(args: OddsArgs) {
+    let hole_cards = [args.hole_cards_0.unwrap(), args.hole_cards_1.unwrap()];
+    let community_cards = args.community_cards;
+    let n = args.n_players.unwrap_or(2);
+    let scenario =
+        Scenario::new(&hole_cards, &community_cards, n, &[]).unwrap_or_else(|e: ScenarioError| {
+            Cli::command()
+                .error(ErrorKind::ValueValidation, e.to_string())
+                .exit()
+        });
+    let stage = scenario.stage();
+    if args.win_rate {
+        return print_win_rate(&stage, hole_cards, &community_cards, args.format);
+    }
+    let mut cache = args.cache.as_deref().map(load_cache);
+    let key = CanonicalKey::from(&scenario);
+    let odds = match cache.as_mut().and_then(|x| x.get(&key)) {
+        Some(odds) => odds,
+        None => {
+            let show_progress = std::io::stderr().is_terminal();
+            let odds = stage
+                .win_rate_with_progress(n, &CancelToken::new(), |done, odds| {
+                    if show_progress {
+                        print_progress(done, odds);
+                    }
+                })
+                .unwrap();
+            if show_progress {
+                eprint!("\r{:width$}\r", "", width = PROGRESS_WIDTH + 40);
+            }
+            if let (Some(cache), Some(path)) = (cache.as_mut(), &args.cache) {
+                cache.insert(key, odds.clone());
+                save_cache(cache, path);
+            }
+            odds
+        }
+    };
+    match args.format {
+        Format::Table => {
+            println!("{} Players", n);
+            println!("{}", &stage);
+            println!("{}", odds);
+        }
+        Format::Json => {
+            let report = Report {
+                n_players: n,
+                hole_cards,
+                community_cards: &community_cards,
+                odds: &odds,
+            };
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+        Format::Csv => {
+            println!("{}", csv_header().join(","));
+            println!("{}", csv_row(n, &odds).join(","));
+        }
+    }
+}
+
+fn print_win_rate(stage: &Stage, hole_cards: [Card; 2], community_cards: &[Card], format: Format) {
+    let win_rate = stage.win_rate();
+    match format {
+        Format::Table => {
+            println!("{}", stage);
+            println!(
+                "win rate against 1 random hand: mean {:.2}%, tie {:.2}%",
+                win_rate.mean * 100.,
+                win_rate.mean_tie_rate * 100.
+            );
+            println!(
+                "min {:.2}%, 25% {:.2}%, median {:.2}%, 75% {:.2}%, max {:.2}%, std {:.2}%",
+                win_rate.min * 100.,
+                win_rate.percentile25 * 100.,
+                win_rate.median * 100.,
+                win_rate.percentile75 * 100.,
+                win_rate.max * 100.,
+                win_rate.std * 100.
+            );
+            for hand in HandType::ALL {
+                let mine = win_rate.self_rate.get(&hand).unwrap_or(&0.);
+                let other = win_rate.other_rate.get(&hand).unwrap_or(&0.);
+                if *mine > 0. || *other > 0. {
+                    println!("{hand}: {:.2}% vs {:.2}%", mine * 100., other * 100.);
+                }
+            }
+        }
+        Format::Json => {
+            let report = WinRateReport {
+                hole_cards,
+                community_cards,
+                win_rate: &win_rate,
+            };
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+        Format::Csv => {
+            println!("{}", win_rate_csv_header().join(","));
+            println!("{}", win_rate_csv_row(&win_rate).join(","));
+        }
+    }
+}
+
+const PROGRESS_WIDTH: usize = 40;
+
+/// `[#######             ]  35% win ~ 61.20%` on stderr
+fn print_progress(done: f64, odds: &Odds) {
+    let filled = (done * PROGRESS_WIDTH as f64) as usize;
+    eprint!(
+        "\r[{}{}] {:>3.0}% win ~ {:.2}%",
+        "#".repeat(filled),
+        " ".repeat(PROGRESS_WIDTH - filled),
+        done * 100.,
+        odds.win * 100.
+    );
+    let _ = std::io::stderr().flush();
+}
+
+fn eval(cards: &[Card]) {
+    check_distinct(cards);
+    let (hand, five) = get_best_hand(cards);
+    println!("{}", print_cards(&five));
+    println!("{} -- {}", hand.hand_type(), hand_type_zh(hand.hand_type()));
+    println!("{}", hand);
+}
+
+fn compare(hands: &[[Card; 2]], board: &[Card]) {
+    check_distinct(hands.iter().flatten().chain(board));
+    if !(board.is_empty() || board.len() >= 3) {
+        Cli::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!("Invalid community cards length {}", board.len()),
+            )
+            .exit();
+    }
+    let shares = showdown_equity(hands, board);
+    for (hand, share) in hands.iter().zip(shares) {
+        print!("{}: {:.2}%", print_cards(hand), share * 100.);
+        if !board.is_empty() {
+            let cards: Vec<Card> = hand.iter().chain(board).copied().collect();
+            print!(", {}", get_best_hand(&cards).0);
+        }
+        println!();
+    }
+}
+
+fn range(
+    hole_cards: [Card; 2],
+    spec: &str,
+    community_cards: &[Card],
+    iterations: usize,
+    seed: u64,
+) {
+    let scenario =
+        Scenario::new(&hole_cards, community_cards, 2, &[]).unwrap_or_else(|e: ScenarioError| {
+            Cli::command()
+                .error(ErrorKind::ValueValidation, e.to_string())
+                .exit()
+        });
+    let range: Range = spec.parse().unwrap_or_else(|e: ParseRangeError| {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, e.to_string())
+            .exit()
+    });
+    let known: Vec<&Card> = hole_cards.iter().chain(community_cards).collect();
+    if range
+        .combos()
+        .iter()
+        .all(|x| x.iter().any(|x| known.contains(&x)))
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ValueValidation,
+                "No hand of the range is possible",
+            )
+            .exit();
+    }
+    let stage = scenario.stage();
+    println!("{}", &stage);
+    println!("vs {} combos", range.len());
+    let odds = equity_vs_range(hole_cards, &range, community_cards, iterations, seed);
+    println!("equity: {:.2}%", (odds.win + odds.tie / 2.) * 100.);
+    println!("{}", odds);
+}
+
+fn outs(hole_cards: [Card; 2], community_cards: &[Card], vs: Option<[Card; 2]>) {
+    check_distinct(
+        hole_cards
+            .iter()
+            .chain(community_cards)
+            .chain(vs.iter().flatten()),
+    );
+    match vs {
+        Some(other) => {
+            let outs = outs_against(hole_cards, other, community_cards);
+            println!("{} outs -- {} 张补牌", outs.len(), outs.len());
+            println!("{}", print_cards(&outs));
+        }
+        None => {
+            let outs = texas_odds::outs::outs(hole_cards, community_cards);
+            println!("{} outs -- {} 张补牌", outs.len(), outs.len());
+            for hand in HandType::ALL {
+                let cards: Vec<Card> = outs
+                    .iter()
+                    .filter(|x| x.hand_type == hand)
+                    .map(|x| x.card)
+                    .collect();
+                if !cards.is_empty() {
+                    println!(
+                        "{} -- {} ({}): {}",
+                        hand,
+                        hand_type_zh(hand),
+                        cards.len(),
+                        print_cards(&cards)
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn chart(n_players: usize, format: ChartFormat, output: Option<PathBuf>) {
+    if n_players < 2 {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, "n_players must be >= 2")
+            .exit();
+    }
+    let chart = preflop_chart(n_players);
+    let text = match format {
+        ChartFormat::Table => {
+            let colored = output.is_none() && std::io::stdout().is_terminal();
+            format!("{} Players\n{}", n_players, chart.to_table(colored))
+        }
+        ChartFormat::Csv => chart.to_csv(),
+        ChartFormat::Svg => chart.to_svg(),
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, text) {
+                eprintln!("Cannot write {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        }
+        None => print!("{text}"),
+    }
+}
+
+fn load_cache(path: &Path) -> OddsCache {
+    let mut cache = OddsCache::default();
+    if let Err(e) = cache.load(path) {
+        eprintln!("Cannot read the cache {}: {e}", path.display());
+        std::process::exit(1);
+    }
+    cache
+}
+
+fn save_cache(cache: &OddsCache, path: &Path) {
+    if let Err(e) = cache.save(path) {
+        eprintln!("Cannot write the cache {}: {e}", path.display());
+        std::process::exit(1);
+    }
+}
+
+/// The odds of `scenario`, without holding the lock during the computation
+fn cached_odds(cache: &Mutex<OddsCache>, scenario: &Scenario) -> Odds {
+    let key = CanonicalKey::from(scenario);
+    if let Some(odds) = cache.lock().unwrap().get(&key) {
+        return odds;
+    }
+    let odds = scenario.odds();
+    cache.lock().unwrap().insert(key, odds.clone());
+    odds
+}
+
+fn batch(input: Option<PathBuf>, jobs: usize, format: Format, cache_path: Option<PathBuf>) {
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) if path.as_os_str() != "-" => match std::fs::File::open(&path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => {
+                eprintln!("Cannot open {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        },
+        _ => Box::new(std::io::stdin().lock()),
+    };
+    let mut out = std::io::stdout().lock();
+    if let Format::Csv = format {
+        let mut header = vec!["line".to_string()];
+        header.extend(csv_header());
+        header.push("error".into());
+        writeln!(out, "{}", header.join(",")).unwrap();
+    }
+
+    let scenarios = reader.lines().enumerate().filter_map(|(i, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Cannot read line {}: {e}", i + 1);
+                std::process::exit(1);
+            }
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return None;
+        }
+        Some((i + 1, trimmed.parse::<Scenario>()))
+    });
+
+    // batches often repeat a spot, or the same spot with other suits
+    let cache = Mutex::new(match &cache_path {
+        Some(path) => load_cache(path),
+        None => OddsCache::default(),
+    });
+    if jobs <= 1 {
+        for (line, scenario) in scenarios {
+            let result = scenario.map(|x| {
+                let odds = cached_odds(&cache, &x);
+                (x, odds)
+            });
+            write_result(&mut out, format, line, &result);
+        }
+    } else {
+        batch_parallel(&mut out, format, jobs, scenarios.collect(), &cache);
+    }
+    if let Some(path) = cache_path {
+        save_cache(&cache.into_inner().unwrap(), &path);
+    }
+}
+
+fn batch_parallel(
+    out: &mut impl Write,
+    format: Format,
+    jobs: usize,
+    scenarios: Vec<(usize, Result<Scenario, ScenarioError>)>,
+    cache: &Mutex<OddsCache>,
+) {
+    // evaluate in parallel, but keep the output in input order
+    let results: Vec<Mutex<Option<_>>> = scenarios.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((_, scenario)) = scenarios.get(i) else {
+                    break;
+                };
+                let result = scenario.clone().map(|x| {
+                    let odds = cached_odds(cache, &x);
+                    (x, odds)
+                });
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    for ((line, _), result) in scenarios.iter().zip(results) {
+        let result = result.into_inner().unwrap().unwrap();
+        write_result(out, format, *line, &result);
+    }
+}
+
+fn write_result(
+    out: &mut impl Write,
+    format: Format,
+    line: usize,
+    result: &Result<(Scenario, Odds), ScenarioError>,
+) {
+    match (format, result) {
+        (Format::Table, Ok((scenario, odds))) => writeln!(
+            out,
+            "#{line} {} Players, {}, dead_cards: {:?}, win: {:.2}%, tie: {:.2}%",
+            scenario.n_players,
+            scenario.stage(),
+            scenario.dead_cards,
+            odds.win * 100.,
+            odds.tie * 100.
+        ),
+        (Format::Table, Err(e)) => writeln!(out, "#{line} error: {e}"),
+        (Format::Json, Ok((scenario, odds))) => {
+            let report = BatchReport {
+                line,
+                report: Report {
+                    n_players: scenario.n_players,
+                    hole_cards: scenario.hole_cards,
+                    community_cards: &scenario.community_cards,
+                    odds,
+                },
+                dead_cards: &scenario.dead_cards,
+            };
+            writeln!(out, "{}", serde_json::to_string(&report).unwrap())
+        }
+        (Format::Json, Err(e)) => {
+            let error = BatchError {
+                line,
+                error: &e.message,
+            };
+            writeln!(out, "{}", serde_json::to_string(&error).unwrap())
+        }
+        (Format::Csv, Ok((scenario, odds))) => {
+            let row = csv_row(scenario.n_players, odds);
+            writeln!(out, "{line},{},", row.join(","))
+        }
+        (Format::Csv, Err(e)) => {
+            let empty = ",".repeat(csv_header().len());
+            writeln!(out, "{line}{empty},\"{}\"", e.message.replace('"', "\"\""))
+        }
+    }
+    .unwrap();
+}
@@ -0,0 +1,246 @@
+//! Interactive mode that keeps the hand between commands and prints the odds after each change.
+use std::io::{BufRead, Write};
+
+use texas_odds::{
+    scenario::{check_spot, Scenario},
+    texas::Card,
+};
+
+const HELP: &str = "\
+Commands -- 命令:
+  hand <card> <card>       set the hole cards -- 设置手牌
+  flop <card> <card> <card>  deal the flop -- 翻牌
+  turn <card>              deal the turn -- 转牌
+  river <card>             deal the river -- 河牌
+  board [card...]          replace the community cards -- 设置公开池
+  players <n>              set the number of players -- 设置玩家数
+  undo                     undo the last change -- 撤销
+  reset                    clear the hand and the board -- 重置
+  show                     print the odds again -- 显示
+  help                     print this message -- 帮助
+  quit                     exit -- 退出";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct State {
+    hole_cards: Option<[Card; 2]>,
+    board: Vec<Card>,
+    n_players: usize,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            hole_cards: None,
+            board: Vec::new(),
+            n_players: 2,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Changed,
+    Show,
+    Help,
+    Quit,
+    Nothing,
+}
+
+#[derive(Default)]
+struct Repl {
+    state: State,
+    history: Vec<State>,
+}
+
+fn parse_cards(args: &[&str], n: usize) -> Result<Vec<Card>, String> {
+    if args.len() != n {
+        return Err(format!("Expected {n} cards, got {}", args.len()));
+    }
+    args.iter()
+        .map(|x| x.parse::<Card>().map_err(|e| e.to_string()))
+        .collect()
+}
+
+impl Repl {
+    fn exec(&mut self, line: &str) -> Result<Outcome, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(Outcome::Nothing);
+        };
+        let mut next = self.state.clone();
+        match *command {
+            "hand" | "hole" => next.hole_cards = Some(parse_cards(args, 2)?.try_into().unwrap()),
+            "flop" => {
+                if !next.board.is_empty() {
+                    return Err("The flop is already dealt, use `board` to change it".into());
+                }
+                next.board = parse_cards(args, 3)?;
+            }
+            "turn" => {
+                if next.board.len() != 3 {
+                    return Err("The turn comes after the flop".into());
+                }
+                next.board.extend(parse_cards(args, 1)?);
+            }
+            "river" => {
+                if next.board.len() != 4 {
+                    return Err("The river comes after the turn".into());
+                }
+                next.board.extend(parse_cards(args, 1)?);
+            }
+            "board" => next.board = parse_cards(args, args.len())?,
+            "players" | "n" => {
+                next.n_players = match args {
+                    [n] => n
+                        .parse()
+                        .map_err(|_| format!("Invalid number of players {n}"))?,
+                    _ => return Err("Usage: players <n>".into()),
+                }
+            }
+            "reset" => next = State::default(),
+            "undo" => {
+                return match self.history.pop() {
+                    Some(prev) => {
+                        self.state = prev;
+                        Ok(Outcome::Changed)
+                    }
+                    None => Err("Nothing to undo".into()),
+                }
+            }
+            "show" | "odds" => return Ok(Outcome::Show),
+            "help" | "?" => return Ok(Outcome::Help),
+            "quit" | "exit" | "q" => return Ok(Outcome::Quit),
+            x => return Err(format!("Unknown command {x}, type `help` for the commands")),
+        }
+
+        let hole_cards = next.hole_cards.as_ref().map_or(&[][..], |x| x.as_slice());
+        check_spot(hole_cards, &next.board, next.n_players, &[]).map_err(|e| e.message)?;
+        if next != self.state {
+            self.history.push(std::mem::replace(&mut self.state, next));
+        }
+        Ok(Outcome::Changed)
+    }
+
+    fn print_odds(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let State {
+            hole_cards,
+            board,
+            n_players,
+        } = &self.state;
+        match hole_cards {
+            Some(hole_cards) => {
+                let scenario = Scenario::new(hole_cards, board, *n_players, &[]).unwrap();
+                writeln!(out, "{} Players", n_players)?;
+                writeln!(out, "{}", scenario.stage())?;
+                writeln!(out, "{}", scenario.odds())
+            }
+            None => writeln!(
+                out,
+                "{} Players, community_cards: {:?}, set the hole cards with `hand`",
+                n_players, board
+            ),
+        }
+    }
+}
+
+pub fn run(input: impl BufRead, mut out: impl Write) -> std::io::Result<()> {
+    let mut repl = Repl::default();
+    writeln!(out, "{HELP}")?;
+    write!(out, "> ")?;
+    out.flush()?;
+    for line in input.lines() {
+        match repl.exec(&line?) {
+            Ok(Outcome::Changed | Outcome::Show) => repl.print_odds(&mut out)?,
+            Ok(Outcome::Help) => writeln!(out, "{HELP}")?,
+            Ok(Outcome::Quit) => return Ok(()),
+            Ok(Outcome::Nothing) => {}
+            Err(e) => writeln!(out, "error: {e}")?,
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, Outcome, Repl};
+    use texas_odds::texas::Card;
+
+    #[test]
+    fn streets_and_undo() {
+        let mut repl = Repl::default();
+        assert_eq!(repl.exec("hand hA dA"), Ok(Outcome::Changed));
+        assert!(repl.exec("turn c2").is_err());
+        repl.exec("flop cK sQ h2").unwrap();
+        repl.exec("turn d7").unwrap();
+        repl.exec("river s9").unwrap();
+        assert_eq!(repl.state.board.len(), 5);
+        assert!(repl.exec("river s8").is_err());
+
+        repl.exec("players 6").unwrap();
+        assert_eq!(repl.state.n_players, 6);
+        repl.exec("undo").unwrap();
+        assert_eq!(repl.state.n_players, 2);
+        repl.exec("undo").unwrap();
+        assert_eq!(repl.state.board.len(), 4);
+
+        repl.exec("reset").unwrap();
+        assert_eq!(repl.state.hole_cards, None);
+        repl.exec("undo").unwrap();
+        assert_eq!(
+            repl.state.hole_cards,
+            Some([Card::from("hA"), Card::from("dA")])
+        );
+    }
+
+    #[test]
+    fn invalid_commands_keep_the_state() {
+        let mut repl = Repl::default();
+        repl.exec("hand hA dA").unwrap();
+        assert_eq!(
+            repl.exec("flop hA sQ h2"),
+            Err("♥️A is used twice".to_string())
+        );
+        assert!(repl.exec("flop x1 sQ h2").is_err());
+        assert!(repl.exec("players 1").is_err());
+        assert!(repl.exec("fold").is_err());
+        assert!(repl.state.board.is_empty());
+        assert_eq!(repl.history.len(), 1);
+        assert_eq!(repl.exec("   "), Ok(Outcome::Nothing));
+    }
+
+    #[test]
+    fn board_without_hole_cards() {
+        let mut repl = Repl::default();
+        assert_eq!(
+            repl.exec("board hA dA"),
+            Err("Invalid community cards length 2".to_string())
+        );
+        assert_eq!(
+            repl.exec("flop hA sQ hA"),
+            Err("♥️A is used twice".to_string())
+        );
+        assert!(repl.exec("board hA sQ h2 d7 c9 s3").is_err());
+        assert!(repl.state.board.is_empty());
+        repl.exec("board hA sQ h2 d7").unwrap();
+        assert!(repl.exec("river d7").is_err());
+        repl.exec("river c9").unwrap();
+        assert_eq!(repl.state.board.len(), 5);
+    }
+
+    #[test]
+    fn session() {
+        let mut out = Vec::new();
+        run(
+            "hand hA dA\nflop hK h2 sQ\nbad\nquit\nplayers 3\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("win: ").count(), 2);
+        assert!(out.contains("community_cards: [♥️K, ♥️2, ♠️Q]"));
+        assert!(out.contains("error: Unknown command bad"));
+        assert!(!out.contains("3 Players"));
+    }
+}
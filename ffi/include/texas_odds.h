@@ -0,0 +1,109 @@
+/*
+ * C API of texas-odds: card parsing, hand evaluation and hold'em odds.
+ *
+ * Every function returns a texas_status, TEXAS_OK on success. Nothing is written to the outputs
+ * on failure. The library never aborts the caller: a panic inside it is reported as
+ * TEXAS_ERR_INTERNAL.
+ */
+#ifndef TEXAS_ODDS_H
+#define TEXAS_ODDS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum texas_status {
+    TEXAS_OK = 0,
+    /* A required pointer is NULL */
+    TEXAS_ERR_NULL = 1,
+    /* A card string or a card struct is not a valid card */
+    TEXAS_ERR_INVALID_CARD = 2,
+    /* Wrong number of cards */
+    TEXAS_ERR_INVALID_COUNT = 3,
+    /* The same card is used twice */
+    TEXAS_ERR_DUPLICATE_CARD = 4,
+    /* Fewer than 2 players */
+    TEXAS_ERR_INVALID_PLAYERS = 5,
+    /* The output buffer is too small */
+    TEXAS_ERR_BUFFER_TOO_SMALL = 6,
+    /* A bug in the library */
+    TEXAS_ERR_INTERNAL = 7,
+} texas_status;
+
+enum {
+    TEXAS_HEART = 0,
+    TEXAS_DIAMOND = 1,
+    TEXAS_CLUB = 2,
+    TEXAS_SPADE = 3,
+};
+
+/* Hand types, from the weakest */
+enum {
+    TEXAS_HIGH_CARD = 0,
+    TEXAS_PAIR = 1,
+    TEXAS_TWO_PAIR = 2,
+    TEXAS_THREE_OF_A_KIND = 3,
+    TEXAS_STRAIGHT = 4,
+    TEXAS_FLUSH = 5,
+    TEXAS_FULL_HOUSE = 6,
+    TEXAS_FOUR_OF_A_KIND = 7,
+    TEXAS_STRAIGHT_FLUSH = 8,
+    TEXAS_ROYAL_FLUSH = 9,
+};
+
+#define TEXAS_HAND_TYPES 10
+
+typedef struct texas_card {
+    /* TEXAS_HEART, TEXAS_DIAMOND, TEXAS_CLUB or TEXAS_SPADE */
+    uint8_t suit;
+    /* 0 for a two up to 12 for an ace */
+    uint8_t num;
+} texas_card;
+
+typedef struct texas_hand {
+    /* TEXAS_HIGH_CARD to TEXAS_ROYAL_FLUSH */
+    uint8_t hand_type;
+    /* Orders the hands, a higher rank wins and equal ranks tie */
+    uint32_t rank;
+} texas_hand;
+
+typedef struct texas_odds {
+    double win;
+    double tie;
+    /* Rate of each of our final hand types, indexed by TEXAS_HIGH_CARD to TEXAS_ROYAL_FLUSH */
+    double hand_rates[TEXAS_HAND_TYPES];
+} texas_odds;
+
+/* A static description of the status */
+const char *texas_status_message(int status);
+
+/* A static name of the hand type, e.g. "FullHouse", or NULL */
+const char *texas_hand_type_name(int hand_type);
+
+/* Parses a card like "hA", "1A", "s10" or "dT": the suit first (h/d/c/s or 1-4), then the number */
+texas_status texas_parse_card(const char *s, texas_card *out);
+
+/* Writes the card like "hA" with the ending NUL, `len` should be at least 3 */
+texas_status texas_format_card(texas_card card, char *buf, size_t len);
+
+/* The best hand of 5 to 7 cards */
+texas_status texas_evaluate(const texas_card *cards, size_t n, texas_hand *out);
+
+/* Compares the best hands of two sets of 5 to 7 cards: `out` is 1 if `a` wins, -1 if `b` wins and
+ * 0 for a tie */
+texas_status texas_compare(const texas_card *a, size_t n_a, const texas_card *b, size_t n_b,
+                           int *out);
+
+/* Odds of the two `hole` cards against `n_players - 1` random hands, with 0, 3, 4 or 5 `board`
+ * cards. `board` may be NULL when `n_board` is 0. */
+texas_status texas_odds_calc(const texas_card *hole, const texas_card *board, size_t n_board,
+                             size_t n_players, texas_odds *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,367 @@
+//! C ABI of texas-odds, declared in `include/texas_odds.h`.
+//!
+//! Invalid input is reported with a `TexasStatus` instead of a panic, and panics never unwind
+//! into the caller.
+#![allow(clippy::missing_safety_doc)]
+use std::{
+    ffi::{c_char, c_int, CStr},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use texas_odds::{
+    odds::{get_best_hand, Stage},
+    texas::{Card, CardNum, HandType, Suit},
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TexasStatus {
+    Ok = 0,
+    ErrNull = 1,
+    ErrInvalidCard = 2,
+    ErrInvalidCount = 3,
+    ErrDuplicateCard = 4,
+    ErrInvalidPlayers = 5,
+    ErrBufferTooSmall = 6,
+    ErrInternal = 7,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TexasCard {
+    pub suit: u8,
+    /// 0 for a two up to 12 for an ace
+    pub num: u8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TexasHand {
+    pub hand_type: u8,
+    pub rank: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TexasOdds {
+    pub win: f64,
+    pub tie: f64,
+    pub hand_rates: [f64; 10],
+}
+
+const SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Club, Suit::Spade];
+
+const NUMS: [CardNum; 13] = [
+    CardNum::Two,
+    CardNum::Three,
+    CardNum::Four,
+    CardNum::Five,
+    CardNum::Six,
+    CardNum::Seven,
+    CardNum::Eight,
+    CardNum::Nine,
+    CardNum::Ten,
+    CardNum::Jack,
+    CardNum::Queen,
+    CardNum::King,
+    CardNum::Ace,
+];
+
+impl TryFrom<TexasCard> for Card {
+    type Error = TexasStatus;
+
+    fn try_from(value: TexasCard) -> Result<Self, Self::Error> {
+        Ok(Card {
+            suit: *SUITS
+                .get(value.suit as usize)
+                .ok_or(TexasStatus::ErrInvalidCard)?,
+            num: *NUMS
+                .get(value.num as usize)
+                .ok_or(TexasStatus::ErrInvalidCard)?,
+        })
+    }
+}
+
+impl From<Card> for TexasCard {
+    fn from(value: Card) -> Self {
+        TexasCard {
+            suit: SUITS.iter().position(|x| *x == value.suit).unwrap() as u8,
+            num: value.num as u8,
+        }
+    }
+}
+
+/// Runs `f`, turning its error and any panic into a status
+fn guard(f: impl FnOnce() -> Result<(), TexasStatus>) -> TexasStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => TexasStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => TexasStatus::ErrInternal,
+    }
+}
+
+/// `n` distinct cards from a C array
+unsafe fn read_cards(
+    cards: *const TexasCard,
+    n: usize,
+    range: std::ops::RangeInclusive<usize>,
+) -> Result<Vec<Card>, TexasStatus> {
+    if !range.contains(&n) {
+        return Err(TexasStatus::ErrInvalidCount);
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    if cards.is_null() {
+        return Err(TexasStatus::ErrNull);
+    }
+    let cards = std::slice::from_raw_parts(cards, n)
+        .iter()
+        .map(|x| Card::try_from(*x))
+        .collect::<Result<Vec<_>, _>>()?;
+    check_distinct(&cards)?;
+    Ok(cards)
+}
+
+fn check_distinct(cards: &[Card]) -> Result<(), TexasStatus> {
+    for (i, card) in cards.iter().enumerate() {
+        if cards[..i].contains(card) {
+            return Err(TexasStatus::ErrDuplicateCard);
+        }
+    }
+    Ok(())
+}
+
+fn evaluate(cards: &[Card]) -> TexasHand {
+    let (hand, _) = get_best_hand(cards);
+    TexasHand {
+        hand_type: hand.hand_type() as u8,
+        rank: hand.rank(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn texas_status_message(status: c_int) -> *const c_char {
+    let message: &'static CStr = match status {
+        0 => c"ok",
+        1 => c"a required pointer is null",
+        2 => c"invalid card",
+        3 => c"invalid number of cards",
+        4 => c"a card is used twice",
+        5 => c"the number of players must be at least 2",
+        6 => c"the buffer is too small",
+        7 => c"internal error",
+        _ => c"unknown status",
+    };
+    message.as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn texas_hand_type_name(hand_type: c_int) -> *const c_char {
+    let name: &'static CStr = match usize::try_from(hand_type)
+        .ok()
+        .and_then(|x| HandType::ALL.get(x))
+    {
+        Some(HandType::HighCard) => c"HighCard",
+        Some(HandType::Pair) => c"Pair",
+        Some(HandType::TwoPair) => c"TwoPair",
+        Some(HandType::ThreeOfAKind) => c"ThreeOfAKind",
+        Some(HandType::Straight) => c"Straight",
+        Some(HandType::Flush) => c"Flush",
+        Some(HandType::FullHouse) => c"FullHouse",
+        Some(HandType::FourOfAKind) => c"FourOfAKind",
+        Some(HandType::StraightFlush) => c"StraightFlush",
+        Some(HandType::RoyalFlush) => c"RoyalFlush",
+        None => return std::ptr::null(),
+    };
+    name.as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn texas_parse_card(s: *const c_char, out: *mut TexasCard) -> TexasStatus {
+    guard(|| {
+        if s.is_null() || out.is_null() {
+            return Err(TexasStatus::ErrNull);
+        }
+        let s = CStr::from_ptr(s)
+            .to_str()
+            .map_err(|_| TexasStatus::ErrInvalidCard)?;
+        let card: Card = s.parse().map_err(|_| TexasStatus::ErrInvalidCard)?;
+        *out = card.into();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn texas_format_card(
+    card: TexasCard,
+    buf: *mut c_char,
+    len: usize,
+) -> TexasStatus {
+    guard(|| {
+        if buf.is_null() {
+            return Err(TexasStatus::ErrNull);
+        }
+        Card::try_from(card)?;
+        if len < 3 {
+            return Err(TexasStatus::ErrBufferTooSmall);
+        }
+        let text = [
+            b"hdcs"[card.suit as usize],
+            b"23456789TJQKA"[card.num as usize],
+            0,
+        ];
+        std::ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buf, text.len());
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn texas_evaluate(
+    cards: *const TexasCard,
+    n: usize,
+    out: *mut TexasHand,
+) -> TexasStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(TexasStatus::ErrNull);
+        }
+        let cards = read_cards(cards, n, 5..=7)?;
+        *out = evaluate(&cards);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn texas_compare(
+    a: *const TexasCard,
+    n_a: usize,
+    b: *const TexasCard,
+    n_b: usize,
+    out: *mut c_int,
+) -> TexasStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(TexasStatus::ErrNull);
+        }
+        let a = evaluate(&read_cards(a, n_a, 5..=7)?);
+        let b = evaluate(&read_cards(b, n_b, 5..=7)?);
+        *out = a.rank.cmp(&b.rank) as c_int;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn texas_odds_calc(
+    hole: *const TexasCard,
+    board: *const TexasCard,
+    n_board: usize,
+    n_players: usize,
+    out: *mut TexasOdds,
+) -> TexasStatus {
+    guard(|| {
+        if hole.is_null() || out.is_null() {
+            return Err(TexasStatus::ErrNull);
+        }
+        let hole = read_cards(hole, 2, 2..=2)?;
+        let board = read_cards(board, n_board, 0..=5)?;
+        if n_board == 1 || n_board == 2 {
+            return Err(TexasStatus::ErrInvalidCount);
+        }
+        check_distinct(&[hole.as_slice(), &board].concat())?;
+        if n_players < 2 {
+            return Err(TexasStatus::ErrInvalidPlayers);
+        }
+        let odds = Stage::new([hole[0], hole[1]], &board).win_rate_with_n_players(n_players);
+        let mut hand_rates = [0.; 10];
+        for (rate, hand_type) in hand_rates.iter_mut().zip(HandType::ALL) {
+            *rate = odds.hand_rate.get(&hand_type).copied().unwrap_or(0.);
+        }
+        *out = TexasOdds {
+            win: odds.win,
+            tie: odds.tie,
+            hand_rates,
+        };
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn card(s: &str) -> TexasCard {
+        let s = std::ffi::CString::new(s).unwrap();
+        let mut out = TexasCard { suit: 0, num: 0 };
+        assert_eq!(
+            unsafe { texas_parse_card(s.as_ptr(), &mut out) },
+            TexasStatus::Ok
+        );
+        out
+    }
+
+    #[test]
+    fn parse_and_format() {
+        assert_eq!(card("hA"), TexasCard { suit: 0, num: 12 });
+        assert_eq!(card("410"), TexasCard { suit: 3, num: 8 });
+        let mut buf = [0 as c_char; 3];
+        let status = unsafe { texas_format_card(card("dT"), buf.as_mut_ptr(), buf.len()) };
+        assert_eq!(status, TexasStatus::Ok);
+        let text = unsafe { CStr::from_ptr(buf.as_ptr()) };
+        assert_eq!(text.to_str().unwrap(), "dT");
+
+        let mut out = TexasCard { suit: 0, num: 0 };
+        let bad = std::ffi::CString::new("x9").unwrap();
+        assert_eq!(
+            unsafe { texas_parse_card(bad.as_ptr(), &mut out) },
+            TexasStatus::ErrInvalidCard
+        );
+        assert_eq!(
+            unsafe { texas_parse_card(std::ptr::null(), &mut out) },
+            TexasStatus::ErrNull
+        );
+        let status = unsafe {
+            texas_format_card(TexasCard { suit: 4, num: 0 }, buf.as_mut_ptr(), buf.len())
+        };
+        assert_eq!(status, TexasStatus::ErrInvalidCard);
+    }
+
+    #[test]
+    fn errors_instead_of_panics() {
+        let cards = [card("hA"), card("dA"), card("cA")];
+        let mut hand = TexasHand {
+            hand_type: 0,
+            rank: 0,
+        };
+        assert_eq!(
+            unsafe { texas_evaluate(cards.as_ptr(), 3, &mut hand) },
+            TexasStatus::ErrInvalidCount
+        );
+        let cards = [card("hA"), card("dA"), card("cA"), card("sA"), card("hA")];
+        assert_eq!(
+            unsafe { texas_evaluate(cards.as_ptr(), 5, &mut hand) },
+            TexasStatus::ErrDuplicateCard
+        );
+
+        let mut odds = TexasOdds {
+            win: 0.,
+            tie: 0.,
+            hand_rates: [0.; 10],
+        };
+        let hole = [card("hA"), card("dA")];
+        let board = [card("c2"), card("d7")];
+        assert_eq!(
+            unsafe { texas_odds_calc(hole.as_ptr(), board.as_ptr(), 2, 2, &mut odds) },
+            TexasStatus::ErrInvalidCount
+        );
+        let board = [card("c2"), card("d7"), card("hA")];
+        assert_eq!(
+            unsafe { texas_odds_calc(hole.as_ptr(), board.as_ptr(), 3, 2, &mut odds) },
+            TexasStatus::ErrDuplicateCard
+        );
+        assert_eq!(
+            unsafe { texas_odds_calc(hole.as_ptr(), std::ptr::null(), 0, 1, &mut odds) },
+            TexasStatus::ErrInvalidPlayers
+        );
+    }
+}
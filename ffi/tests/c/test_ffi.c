@@ -0,0 +1,95 @@
+/* Exercises the C API like a C or C++ caller would, exits with a non-zero status on failure */
+#include <stdio.h>
+#include <string.h>
+
+#include "texas_odds.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static texas_card card(const char *s) {
+    texas_card out = {0, 0};
+    texas_status status = texas_parse_card(s, &out);
+    if (status != TEXAS_OK) {
+        fprintf(stderr, "cannot parse %s: %s\n", s, texas_status_message(status));
+        failures++;
+    }
+    return out;
+}
+
+static void test_cards(void) {
+    texas_card ace = card("hA");
+    CHECK(ace.suit == TEXAS_HEART && ace.num == 12);
+
+    char buf[3];
+    CHECK(texas_format_card(card("s10"), buf, sizeof buf) == TEXAS_OK);
+    CHECK(strcmp(buf, "sT") == 0);
+    CHECK(texas_format_card(ace, buf, 2) == TEXAS_ERR_BUFFER_TOO_SMALL);
+
+    texas_card out;
+    CHECK(texas_parse_card("z3", &out) == TEXAS_ERR_INVALID_CARD);
+    CHECK(texas_parse_card(NULL, &out) == TEXAS_ERR_NULL);
+    CHECK(strcmp(texas_status_message(TEXAS_ERR_DUPLICATE_CARD), "a card is used twice") == 0);
+}
+
+static void test_evaluate(void) {
+    texas_card full_house[7] = {card("hK"), card("dK"), card("cK"), card("h3"),
+                                card("d3"), card("s9"), card("s2")};
+    texas_hand hand;
+    CHECK(texas_evaluate(full_house, 7, &hand) == TEXAS_OK);
+    CHECK(hand.hand_type == TEXAS_FULL_HOUSE);
+    CHECK(strcmp(texas_hand_type_name(hand.hand_type), "FullHouse") == 0);
+    CHECK(texas_hand_type_name(42) == NULL);
+
+    texas_card flush[5] = {card("h2"), card("h7"), card("h9"), card("hJ"), card("hA")};
+    texas_hand other;
+    CHECK(texas_evaluate(flush, 5, &other) == TEXAS_OK);
+    CHECK(hand.rank > other.rank);
+
+    int cmp = 0;
+    CHECK(texas_compare(flush, 5, full_house, 7, &cmp) == TEXAS_OK);
+    CHECK(cmp == -1);
+    CHECK(texas_compare(full_house, 7, full_house, 7, &cmp) == TEXAS_OK);
+    CHECK(cmp == 0);
+
+    CHECK(texas_evaluate(flush, 4, &hand) == TEXAS_ERR_INVALID_COUNT);
+    flush[1] = flush[0];
+    CHECK(texas_evaluate(flush, 5, &hand) == TEXAS_ERR_DUPLICATE_CARD);
+}
+
+static void test_odds(void) {
+    texas_card hole[2] = {card("hA"), card("dA")};
+    texas_card board[3] = {card("c2"), card("d7"), card("sQ")};
+    texas_odds odds;
+    CHECK(texas_odds_calc(hole, board, 3, 2, &odds) == TEXAS_OK);
+    CHECK(odds.win > 0.6 && odds.win < 0.8);
+    double total = 0;
+    for (int i = 0; i < TEXAS_HAND_TYPES; i++) {
+        total += odds.hand_rates[i];
+    }
+    CHECK(total > 0.999 && total < 1.001);
+    CHECK(odds.hand_rates[TEXAS_HIGH_CARD] == 0.0);
+
+    CHECK(texas_odds_calc(hole, board, 2, 2, &odds) == TEXAS_ERR_INVALID_COUNT);
+    CHECK(texas_odds_calc(hole, board, 3, 1, &odds) == TEXAS_ERR_INVALID_PLAYERS);
+    CHECK(texas_odds_calc(hole, NULL, 3, 2, &odds) == TEXAS_ERR_NULL);
+}
+
+int main(void) {
+    test_cards();
+    test_evaluate();
+    test_odds();
+    if (failures) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,44 @@
+//! Builds `tests/c/test_ffi.c` against the static library and runs it.
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_program() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // target/<profile>/deps/c_api-<hash>
+    let exe = env::current_exe().unwrap();
+    let target_dir = exe.parent().unwrap().parent().unwrap();
+    // `cargo test` only builds the rlib
+    let mut build = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    build.args(["build", "--offline", "--lib", "-p", "texas-odds-ffi"]);
+    if target_dir.ends_with("release") {
+        build.arg("--release");
+    }
+    assert!(
+        build.status().unwrap().success(),
+        "cannot build the library"
+    );
+    let lib = target_dir.join("libtexas_odds_ffi.a");
+
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_ffi");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .arg(root.join("tests/c/test_ffi.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(&lib)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&out)
+        .status()
+        .expect("a C compiler is needed, set CC to use another one");
+    assert!(status.success(), "cannot compile the C test");
+
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
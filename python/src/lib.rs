@@ -0,0 +1,442 @@
+//! The `texas_odds` Python module.
+//!
+//! Cards are given as `Card` objects, text like `"hA"` or their `Card.index`. Invalid input
+//! raises `ValueError`, and the long calculations release the GIL.
+use std::collections::BTreeMap;
+
+use numpy::{
+    ndarray::ArrayView2, AllowTypeChange, IntoPyArray, PyArray1, PyArrayLike2,
+    PyUntypedArrayMethods,
+};
+use pyo3::{basic::CompareOp, exceptions::PyValueError, prelude::*, types::PyDict};
+use texas_odds::{
+    odds::{get_best_hand, Odds, Stage as OriginStage, DEFAULT_ITERATIONS},
+    range::{equity_vs_range, Range},
+    scenario::Scenario,
+    texas::{Card, CardNum, HandType, Suit},
+};
+
+const SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Club, Suit::Spade];
+
+/// `suit * 13 + num`, with the suits ordered heart, diamond, club, spade and the numbers from
+/// two to ace
+fn card_index(card: Card) -> usize {
+    SUITS.iter().position(|x| *x == card.suit).unwrap() * 13 + card.num as usize
+}
+
+fn card_from_index(index: usize) -> Option<Card> {
+    if index >= 52 {
+        return None;
+    }
+    // `CardNum::from` counts from the ace
+    let num = index % 13;
+    let num = if num == 12 { 1 } else { num + 2 };
+    Some(Card {
+        suit: SUITS[index / 13],
+        num: CardNum::from(num),
+    })
+}
+
+fn check_distinct(cards: &[Card]) -> Result<(), String> {
+    for (i, card) in cards.iter().enumerate() {
+        if cards[..i].contains(card) {
+            return Err(format!("{card} is used twice"));
+        }
+    }
+    Ok(())
+}
+
+/// 5 to 7 distinct cards
+fn check_hand(cards: &[Card]) -> Result<(), String> {
+    if cards.len() < 5 || cards.len() > 7 {
+        return Err(format!("Expected 5 to 7 cards, got {}", cards.len()));
+    }
+    check_distinct(cards)
+}
+
+/// Hand type indices (in `HAND_TYPES`) and ranks of each row of card indices
+fn evaluate_rows(rows: ArrayView2<i64>) -> Result<(Vec<u8>, Vec<u32>), String> {
+    let mut hand_types = Vec::with_capacity(rows.nrows());
+    let mut ranks = Vec::with_capacity(rows.nrows());
+    for (i, row) in rows.rows().into_iter().enumerate() {
+        let cards = row
+            .iter()
+            .map(|&x| {
+                usize::try_from(x)
+                    .ok()
+                    .and_then(card_from_index)
+                    .ok_or_else(|| format!("Invalid card index {x} in row {i}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        check_hand(&cards).map_err(|e| format!("{e} in row {i}"))?;
+        let (hand, _) = get_best_hand(&cards);
+        hand_types.push(hand.hand_type() as u8);
+        ranks.push(hand.rank());
+    }
+    Ok((hand_types, ranks))
+}
+
+fn value_error(message: impl ToString) -> PyErr {
+    PyValueError::new_err(message.to_string())
+}
+
+/// A card, from text like `hA`, `1A` or `d13` (the suit first) or from `Card.from_index`
+#[pyclass(name = "Card", module = "texas_odds", frozen, eq)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct PyCard(Card);
+
+#[pymethods]
+impl PyCard {
+    #[new]
+    fn new(card: &str) -> PyResult<Self> {
+        card.parse().map(PyCard).map_err(value_error)
+    }
+
+    #[staticmethod]
+    fn from_index(index: usize) -> PyResult<Self> {
+        card_from_index(index)
+            .map(PyCard)
+            .ok_or_else(|| value_error(format!("Invalid card index {index}")))
+    }
+
+    /// 0 to 51, see `from_index`
+    #[getter]
+    fn index(&self) -> usize {
+        card_index(self.0)
+    }
+
+    /// `h`, `d`, `c` or `s`
+    #[getter]
+    fn suit(&self) -> &'static str {
+        ["h", "d", "c", "s"][self.index() / 13]
+    }
+
+    /// 0 for a two up to 12 for an ace
+    #[getter]
+    fn num(&self) -> usize {
+        self.0.num as usize
+    }
+
+    fn __hash__(&self) -> usize {
+        self.index()
+    }
+
+    fn __str__(&self) -> String {
+        format!("{}{}", self.suit(), self.0.num)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Card('{}')", self.__str__())
+    }
+}
+
+/// A `Card`, its text or its index
+#[derive(FromPyObject)]
+enum CardLike {
+    Card(PyCard),
+    Index(usize),
+    Text(String),
+}
+
+impl TryFrom<CardLike> for Card {
+    type Error = PyErr;
+
+    fn try_from(value: CardLike) -> PyResult<Self> {
+        match value {
+            CardLike::Card(card) => Ok(card.0),
+            CardLike::Index(index) => PyCard::from_index(index).map(|x| x.0),
+            CardLike::Text(text) => PyCard::new(&text).map(|x| x.0),
+        }
+    }
+}
+
+fn to_cards(cards: Vec<CardLike>) -> PyResult<Vec<Card>> {
+    cards.into_iter().map(Card::try_from).collect()
+}
+
+/// The best hand of 5 to 7 cards, a higher `rank` wins and equal ranks tie
+#[pyclass(name = "Hand", module = "texas_odds", frozen)]
+pub struct PyHand {
+    hand_type: HandType,
+    rank: u32,
+    description: String,
+    cards: [Card; 5],
+}
+
+#[pymethods]
+impl PyHand {
+    /// e.g. `FullHouse`
+    #[getter]
+    fn hand_type(&self) -> String {
+        self.hand_type.to_string()
+    }
+
+    #[getter]
+    fn rank(&self) -> u32 {
+        self.rank
+    }
+
+    /// e.g. `full house, K full of 2`
+    #[getter]
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The five cards that make the hand
+    #[getter]
+    fn cards(&self) -> Vec<PyCard> {
+        self.cards.iter().copied().map(PyCard).collect()
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.rank.cmp(&other.rank))
+    }
+
+    fn __hash__(&self) -> u32 {
+        self.rank
+    }
+
+    fn __str__(&self) -> &str {
+        &self.description
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Hand({}, '{}')", self.hand_type, self.description)
+    }
+}
+
+fn eval_hand(cards: Vec<CardLike>) -> PyResult<PyHand> {
+    let cards = to_cards(cards)?;
+    check_hand(&cards).map_err(value_error)?;
+    let (hand, five) = get_best_hand(&cards);
+    Ok(PyHand {
+        hand_type: hand.hand_type(),
+        rank: hand.rank(),
+        description: hand.to_string(),
+        cards: five,
+    })
+}
+
+/// The best hand of 5 to 7 cards, e.g. `evaluate(["hA", "dA", "cK", "sK", "h2"])`
+#[pyfunction]
+fn evaluate(cards: Vec<CardLike>) -> PyResult<PyHand> {
+    eval_hand(cards)
+}
+
+/// Compares the best hands of two sets of 5 to 7 cards: `1` if `a` wins, `-1` if `b` wins, `0`
+/// for a tie
+#[pyfunction]
+fn compare(a: Vec<CardLike>, b: Vec<CardLike>) -> PyResult<i32> {
+    let a = eval_hand(a)?;
+    let b = eval_hand(b)?;
+    Ok(a.rank.cmp(&b.rank) as i32)
+}
+
+/// Hand types and ranks of `evaluate_batch`
+type Batch<'py> = (Bound<'py, PyArray1<u8>>, Bound<'py, PyArray1<u32>>);
+
+/// Evaluates a 2-D array of any integer dtype with a row of 5 to 7 card indices per hand,
+/// returns the arrays of the hand types (indices in `HAND_TYPES`) and of the ranks
+#[pyfunction]
+fn evaluate_batch<'py>(
+    py: Python<'py>,
+    cards: PyArrayLike2<'py, i64, AllowTypeChange>,
+) -> PyResult<Batch<'py>> {
+    if !(5..=7).contains(&cards.shape()[1]) {
+        return Err(value_error(format!(
+            "Expected 5 to 7 cards per row, got {}",
+            cards.shape()[1]
+        )));
+    }
+    let rows = cards.as_array();
+    let (hand_types, ranks) = py.detach(|| evaluate_rows(rows)).map_err(value_error)?;
+    Ok((hand_types.into_pyarray(py), ranks.into_pyarray(py)))
+}
+
+/// Every hand type with its rate, including the impossible ones
+fn hand_type_rates<'py>(
+    py: Python<'py>,
+    rates: &BTreeMap<HandType, f64>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for hand_type in HandType::ALL {
+        let rate = rates.get(&hand_type).copied().unwrap_or(0.);
+        dict.set_item(hand_type.to_string(), rate)?;
+    }
+    Ok(dict)
+}
+
+fn odds_dict<'py>(py: Python<'py>, odds: &Odds) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("win", odds.win)?;
+    dict.set_item("tie", odds.tie)?;
+    dict.set_item("hand_type_rates", hand_type_rates(py, &odds.hand_rate)?)?;
+    Ok(dict)
+}
+
+/// Our two hole cards and 0, 3, 4 or 5 community cards
+#[pyclass(name = "Stage", module = "texas_odds", frozen)]
+pub struct PyStage {
+    stage: OriginStage,
+}
+
+#[pymethods]
+impl PyStage {
+    #[new]
+    #[pyo3(signature = (hole_cards, community_cards = Vec::new(), dead_cards = Vec::new()))]
+    fn new(
+        hole_cards: Vec<CardLike>,
+        community_cards: Vec<CardLike>,
+        dead_cards: Vec<CardLike>,
+    ) -> PyResult<Self> {
+        let scenario = Scenario::new(
+            &to_cards(hole_cards)?,
+            &to_cards(community_cards)?,
+            2,
+            &to_cards(dead_cards)?,
+        )
+        .map_err(value_error)?;
+        Ok(Self {
+            stage: scenario.stage(),
+        })
+    }
+
+    /// `{"win", "tie", "hand_type_rates"}` against `n_players - 1` random hands
+    #[pyo3(signature = (n_players = 2))]
+    fn odds<'py>(&self, py: Python<'py>, n_players: usize) -> PyResult<Bound<'py, PyDict>> {
+        if n_players < 2 {
+            return Err(value_error("n_players must be >= 2"));
+        }
+        let odds = py.detach(|| self.stage.win_rate_with_n_players(n_players));
+        odds_dict(py, &odds)
+    }
+
+    /// The distribution of the win rate against one random hand
+    fn win_rate<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let rate = py.detach(|| self.stage.win_rate());
+        let dict = PyDict::new(py);
+        dict.set_item("mean", rate.mean)?;
+        dict.set_item("mean_tie_rate", rate.mean_tie_rate)?;
+        dict.set_item("min", rate.min)?;
+        dict.set_item("max", rate.max)?;
+        dict.set_item("percentile25", rate.percentile25)?;
+        dict.set_item("median", rate.median)?;
+        dict.set_item("percentile75", rate.percentile75)?;
+        dict.set_item("std", rate.std)?;
+        dict.set_item("self_rate", hand_type_rates(py, &rate.self_rate)?)?;
+        dict.set_item("other_rate", hand_type_rates(py, &rate.other_rate)?)?;
+        dict.set_item("diff_rate", hand_type_rates(py, &rate.diff_rate)?)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Stage({})", self.stage)
+    }
+}
+
+/// Monte Carlo odds against one opponent holding a hand of `range`, e.g. `"QQ+, AKs"`
+#[pyfunction]
+#[pyo3(signature = (hole_cards, range, community_cards = Vec::new(), iterations = DEFAULT_ITERATIONS, seed = 0))]
+fn range_equity<'py>(
+    py: Python<'py>,
+    hole_cards: Vec<CardLike>,
+    range: &str,
+    community_cards: Vec<CardLike>,
+    iterations: usize,
+    seed: u64,
+) -> PyResult<Bound<'py, PyDict>> {
+    let scenario = Scenario::new(&to_cards(hole_cards)?, &to_cards(community_cards)?, 2, &[])
+        .map_err(value_error)?;
+    let range: Range = range.parse().map_err(value_error)?;
+    let known: Vec<Card> = scenario
+        .hole_cards
+        .iter()
+        .chain(&scenario.community_cards)
+        .copied()
+        .collect();
+    if range
+        .combos()
+        .iter()
+        .all(|x| x.iter().any(|x| known.contains(x)))
+    {
+        return Err(value_error("No hand of the range is possible"));
+    }
+    let odds = py.detach(|| {
+        equity_vs_range(
+            scenario.hole_cards,
+            &range,
+            &scenario.community_cards,
+            iterations,
+            seed,
+        )
+    });
+    odds_dict(py, &odds)
+}
+
+#[pymodule]
+#[pyo3(name = "texas_odds")]
+fn texas_odds_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCard>()?;
+    m.add_class::<PyHand>()?;
+    m.add_class::<PyStage>()?;
+    m.add_function(wrap_pyfunction!(evaluate, m)?)?;
+    m.add_function(wrap_pyfunction!(compare, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(range_equity, m)?)?;
+    let hand_types: Vec<String> = HandType::ALL.iter().map(|x| x.to_string()).collect();
+    m.add("HAND_TYPES", hand_types)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use numpy::ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn index() {
+        for i in 0..52 {
+            assert_eq!(card_index(card_from_index(i).unwrap()), i);
+        }
+        assert_eq!(card_index(Card::from("h2")), 0);
+        assert_eq!(card_index(Card::from("hA")), 12);
+        assert_eq!(card_index(Card::from("s2")), 39);
+        assert!(card_from_index(52).is_none());
+    }
+
+    #[test]
+    fn batch() {
+        let index = |x: &str| card_index(Card::from(x)) as i64;
+        let rows = array![
+            [
+                index("hK"),
+                index("dK"),
+                index("cK"),
+                index("h3"),
+                index("d3")
+            ],
+            [
+                index("h2"),
+                index("h7"),
+                index("h9"),
+                index("hJ"),
+                index("hA")
+            ],
+        ];
+        let (hand_types, ranks) = evaluate_rows(rows.view()).unwrap();
+        assert_eq!(
+            hand_types,
+            [HandType::FullHouse as u8, HandType::Flush as u8]
+        );
+        assert!(ranks[0] > ranks[1]);
+
+        let rows = array![[0, 1, 2, 3, 52]];
+        assert_eq!(
+            evaluate_rows(rows.view()).unwrap_err(),
+            "Invalid card index 52 in row 0"
+        );
+        let rows = array![[0, 1, 2, 3, 3]];
+        assert!(evaluate_rows(rows.view()).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+import pytest
+
+import texas_odds
+from texas_odds import Card, Stage, compare, evaluate, range_equity
+
+
+def test_card():
+    card = Card("hA")
+    assert card == Card("1A")
+    assert (card.suit, card.num, card.index) == ("h", 12, 12)
+    assert Card.from_index(card.index) == card
+    assert repr(Card("s10")) == "Card('s10')"
+    assert len({Card("d2"), Card("d2"), Card("c2")}) == 2
+    with pytest.raises(ValueError, match="Invalid card x9"):
+        Card("x9")
+    with pytest.raises(ValueError):
+        Card.from_index(52)
+
+
+def test_evaluate():
+    hand = evaluate(["hK", "dK", "cK", "h3", "d3", "s9", Card("s2")])
+    assert hand.hand_type == "FullHouse"
+    assert len(hand.cards) == 5
+    flush = evaluate([0, 5, 7, 9, 12])
+    assert flush.hand_type == "Flush"
+    assert hand > flush
+    assert compare(["h2", "h7", "h9", "hJ", "hA"], ["hK", "dK", "cK", "h3", "d3"]) == -1
+    with pytest.raises(ValueError, match="Expected 5 to 7 cards"):
+        evaluate(["hA", "dA"])
+    with pytest.raises(ValueError, match="used twice"):
+        evaluate(["hA", "dA", "cA", "sA", "hA"])
+
+
+def test_stage():
+    stage = Stage(["hA", "dA"], ["c2", "d7", "sQ"])
+    odds = stage.odds()
+    assert 0 < odds["win"] < 1
+    assert sum(odds["hand_type_rates"].values()) == pytest.approx(1)
+    assert list(odds["hand_type_rates"]) == texas_odds.HAND_TYPES
+    assert stage.odds(n_players=3)["win"] < odds["win"]
+    rate = stage.win_rate()
+    assert rate["min"] <= rate["median"] <= rate["max"]
+    with pytest.raises(ValueError):
+        stage.odds(1)
+    with pytest.raises(ValueError, match="used twice"):
+        Stage(["hA", "dA"], ["hA", "d7", "sQ"])
+
+
+def test_range_equity():
+    odds = range_equity(["hA", "dA"], "KK", iterations=2000, seed=1)
+    assert 0.7 < odds["win"] < 0.9
+    assert odds == range_equity(["hA", "dA"], "KK", iterations=2000, seed=1)
+    with pytest.raises(ValueError):
+        range_equity(["hA", "dA"], "AA", community_cards=["cA", "sA", "h2"])
+
+
+def test_evaluate_batch():
+    np = pytest.importorskip("numpy")
+    cards = np.array([[0, 5, 7, 9, 12], [11, 24, 37, 1, 14]])
+    hand_types, ranks = texas_odds.evaluate_batch(cards)
+    assert [texas_odds.HAND_TYPES[x] for x in hand_types] == ["Flush", "FullHouse"]
+    assert ranks[1] > ranks[0]
+    small_types, small_ranks = texas_odds.evaluate_batch(cards.astype(np.int8))
+    assert list(small_types) == list(hand_types)
+    assert list(small_ranks) == list(ranks)
+    with pytest.raises(ValueError, match="row 1"):
+        texas_odds.evaluate_batch(np.array([[0, 1, 2, 3, 4], [0, 1, 2, 3, 3]]))
@@ -0,0 +1,342 @@
+//! The JSON endpoints, apart from the HTTP plumbing.
+//!
+//! Every endpoint takes a JSON object and returns a JSON object, or an `ApiError` whose message is
+//! sent back as `{"error": message}`.
+use std::fmt::Display;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use texas_odds::{
+    odds::{get_best_hand, showdown_equity, Odds, DEFAULT_ITERATIONS},
+    range::{equity_vs_range, ParseRangeError, Range},
+    scenario::{Scenario, ScenarioError},
+    texas::Card,
+};
+
+/// Upper bound of `iterations` for `/range`
+pub const MAX_ITERATIONS: usize = 1_000_000;
+
+/// Upper bound of the players of `/odds` and the hands of `/compare`
+pub const MAX_PLAYERS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// HTTP status code
+    pub status: u16,
+    pub message: String,
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+fn bad_request<T>(message: impl Into<String>) -> Result<T, ApiError> {
+    Err(ApiError {
+        status: 400,
+        message: message.into(),
+    })
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).or_else(|e| bad_request(e.to_string()))
+}
+
+fn parse_cards(cards: &[String]) -> Result<Vec<Card>, ApiError> {
+    cards
+        .iter()
+        .map(|x| x.parse::<Card>())
+        .collect::<Result<_, _>>()
+        .or_else(|e| bad_request(e.to_string()))
+}
+
+fn check_distinct<'a>(cards: impl IntoIterator<Item = &'a Card>) -> Result<(), ApiError> {
+    let mut seen: Vec<&Card> = Vec::new();
+    for card in cards {
+        if seen.contains(&card) {
+            return bad_request(format!("{card} is used twice"));
+        }
+        seen.push(card);
+    }
+    Ok(())
+}
+
+fn check_board(cards: &[Card]) -> Result<(), ApiError> {
+    if !(cards.is_empty() || (3..=5).contains(&cards.len())) {
+        return bad_request(format!("Invalid community cards length {}", cards.len()));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct OddsResponse {
+    n_players: usize,
+    #[serde(flatten)]
+    odds: Odds,
+}
+
+/// Takes the JSON lines of `texas-odds batch`
+fn odds(body: &str) -> Result<OddsResponse, ApiError> {
+    if !body.trim_start().starts_with('{') {
+        return bad_request("Expected a JSON object");
+    }
+    let scenario: Scenario = body
+        .parse()
+        .or_else(|e: ScenarioError| bad_request(e.to_string()))?;
+    if scenario.n_players > MAX_PLAYERS {
+        return bad_request(format!("n_players must be <= {MAX_PLAYERS}"));
+    }
+    Ok(OddsResponse {
+        n_players: scenario.n_players,
+        odds: scenario.odds(),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EvalRequest {
+    cards: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct EvalResponse {
+    hand_type: String,
+    /// e.g. `full house, K full of 2`
+    description: String,
+    /// A higher rank wins, equal ranks tie
+    rank: u32,
+    /// The five cards that make the hand
+    cards: Vec<String>,
+}
+
+fn eval_cards(cards: &[Card]) -> EvalResponse {
+    let (hand, five) = get_best_hand(cards);
+    EvalResponse {
+        hand_type: hand.hand_type().to_string(),
+        description: hand.to_string(),
+        rank: hand.rank(),
+        cards: five.iter().map(|x| x.to_string()).collect(),
+    }
+}
+
+fn eval(body: &str) -> Result<EvalResponse, ApiError> {
+    let request: EvalRequest = parse_body(body)?;
+    let cards = parse_cards(&request.cards)?;
+    if cards.len() < 5 || cards.len() > 7 {
+        return bad_request(format!("Expected 5 to 7 cards, got {}", cards.len()));
+    }
+    check_distinct(&cards)?;
+    Ok(eval_cards(&cards))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompareRequest {
+    hands: Vec<[String; 2]>,
+    #[serde(default)]
+    community_cards: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CompareResponse {
+    /// Share of the pot of each hand
+    equity: Vec<f64>,
+    /// The best hand of each player once the flop is dealt
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hands: Vec<EvalResponse>,
+}
+
+/// Exact equity of known hands head to head, like `texas-odds compare`
+fn compare(body: &str) -> Result<CompareResponse, ApiError> {
+    let request: CompareRequest = parse_body(body)?;
+    if request.hands.len() < 2 || request.hands.len() > MAX_PLAYERS {
+        return bad_request(format!(
+            "Expected 2 to {MAX_PLAYERS} hands, got {}",
+            request.hands.len()
+        ));
+    }
+    let hands = request
+        .hands
+        .iter()
+        .map(|x| parse_cards(x).map(|x| [x[0], x[1]]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let board = parse_cards(&request.community_cards)?;
+    check_board(&board)?;
+    check_distinct(hands.iter().flatten().chain(&board))?;
+    let hand_evals = if board.is_empty() {
+        Vec::new()
+    } else {
+        hands
+            .iter()
+            .map(|x| eval_cards(&[x.as_slice(), &board].concat()))
+            .collect()
+    };
+    Ok(CompareResponse {
+        equity: showdown_equity(&hands, &board),
+        hands: hand_evals,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RangeRequest {
+    hole_cards: Vec<String>,
+    range: String,
+    #[serde(default)]
+    community_cards: Vec<String>,
+    iterations: Option<usize>,
+    #[serde(default)]
+    seed: u64,
+}
+
+#[derive(Serialize)]
+struct RangeResponse {
+    /// `win + tie / 2`
+    equity: f64,
+    /// Hands of the range
+    combos: usize,
+    #[serde(flatten)]
+    odds: Odds,
+}
+
+/// Monte Carlo odds against a range, like `texas-odds range`
+fn range(body: &str) -> Result<RangeResponse, ApiError> {
+    let request: RangeRequest = parse_body(body)?;
+    let iterations = request.iterations.unwrap_or(DEFAULT_ITERATIONS);
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return bad_request(format!("iterations must be in 1..={MAX_ITERATIONS}"));
+    }
+    let scenario = Scenario::new(
+        &parse_cards(&request.hole_cards)?,
+        &parse_cards(&request.community_cards)?,
+        2,
+        &[],
+    )
+    .or_else(|e| bad_request(e.to_string()))?;
+    let range: Range = request
+        .range
+        .parse()
+        .or_else(|e: ParseRangeError| bad_request(e.to_string()))?;
+    let known: Vec<&Card> = scenario
+        .hole_cards
+        .iter()
+        .chain(&scenario.community_cards)
+        .collect();
+    if range
+        .combos()
+        .iter()
+        .all(|x| x.iter().any(|x| known.contains(&x)))
+    {
+        return bad_request("No hand of the range is possible");
+    }
+    let odds = equity_vs_range(
+        scenario.hole_cards,
+        &range,
+        &scenario.community_cards,
+        iterations,
+        request.seed,
+    );
+    Ok(RangeResponse {
+        equity: odds.win + odds.tie / 2.,
+        combos: range.len(),
+        odds,
+    })
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, ApiError> {
+    serde_json::to_string(value).map_err(|e| ApiError {
+        status: 500,
+        message: e.to_string(),
+    })
+}
+
+/// The response body of a `POST` to `path`
+pub fn handle(path: &str, body: &str) -> Result<String, ApiError> {
+    match path {
+        "/odds" => to_json(&odds(body)?),
+        "/eval" => to_json(&eval(body)?),
+        "/compare" => to_json(&compare(body)?),
+        "/range" => to_json(&range(body)?),
+        _ => Err(ApiError {
+            status: 404,
+            message: format!("No endpoint {path}"),
+        }),
+    }
+}
+
+/// Whether `path` is one of the endpoints
+pub fn is_endpoint(path: &str) -> bool {
+    matches!(path, "/odds" | "/eval" | "/compare" | "/range")
+}
+
+/// The cache key of a request: the path and the body with sorted keys and no whitespace, so the
+/// same request written differently hits the cache
+pub fn cache_key(path: &str, body: &str) -> Option<String> {
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    Some(format!("{path} {body}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn error(path: &str, body: &str) -> String {
+        handle(path, body).unwrap_err().message
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(error("/odds", "h2 d3"), "Expected a JSON object");
+        assert_eq!(
+            error("/odds", r#"{"hole_cards": ["h2", "h2"]}"#),
+            "♥️2 is used twice"
+        );
+        assert_eq!(
+            error("/odds", r#"{"hole_cards": ["h2", "d3"], "n_players": 11}"#),
+            "n_players must be <= 10"
+        );
+        assert_eq!(
+            error("/eval", r#"{"cards": ["h2", "d3"]}"#),
+            "Expected 5 to 7 cards, got 2"
+        );
+        assert_eq!(
+            error("/compare", r#"{"hands": [["hA", "dA"]]}"#),
+            "Expected 2 to 10 hands, got 1"
+        );
+        assert_eq!(
+            error(
+                "/compare",
+                r#"{"hands": [["hA", "dA"], ["sK", "cK"]], "community_cards": ["h2"]}"#
+            ),
+            "Invalid community cards length 1"
+        );
+        assert_eq!(
+            error(
+                "/range",
+                r#"{"hole_cards": ["hA", "dA"], "range": "KK", "iterations": 0}"#
+            ),
+            "iterations must be in 1..=1000000"
+        );
+        assert_eq!(
+            error(
+                "/range",
+                r#"{"hole_cards": ["hA", "dA"], "range": "AA", "community_cards": ["cA", "sA", "h2"]}"#
+            ),
+            "No hand of the range is possible"
+        );
+        assert!(error("/eval", r#"{"cards": ["h2"], "extra": 1}"#).contains("unknown field"));
+        assert_eq!(handle("/nope", "{}").unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn cache_key_ignores_layout() {
+        assert_eq!(
+            cache_key("/eval", r#"{"b": 1, "a": [1, 2]}"#),
+            cache_key("/eval", r#"{ "a":[1,2],"b":1 }"#)
+        );
+        assert_ne!(cache_key("/eval", "{}"), cache_key("/odds", "{}"));
+        assert_eq!(cache_key("/eval", "{"), None);
+    }
+}
@@ -0,0 +1,179 @@
+mod api;
+
+use std::{
+    io::{Read, Write},
+    num::NonZeroUsize,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+use clap::Parser;
+use lru::LruCache;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::api::ApiError;
+
+/// Largest accepted request body
+const MAX_BODY: usize = 64 * 1024;
+
+#[derive(Parser)]
+/// HTTP/JSON API of the Texas Hold'em odds calculator
+///
+/// Endpoints, all `POST` with a JSON body: /odds, /eval, /compare and /range.
+#[command(author, version)]
+struct Cli {
+    /// Address to listen on, port 0 picks a free port
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// Number of requests computed in parallel
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+
+    /// Number of requests waiting for a job before new ones are refused with 503
+    #[arg(short, long, default_value_t = 64)]
+    queue: usize,
+
+    /// Number of responses kept in memory, 0 disables the cache
+    #[arg(short, long, default_value_t = 1024)]
+    cache_size: usize,
+}
+
+type Cache = Option<Mutex<LruCache<String, String>>>;
+
+fn main() {
+    let cli = Cli::parse();
+    if cli.jobs == 0 {
+        eprintln!("--jobs must be at least 1");
+        std::process::exit(2);
+    }
+    let server = Server::http(&cli.addr).unwrap_or_else(|e| {
+        eprintln!("Cannot listen on {}: {e}", cli.addr);
+        std::process::exit(1);
+    });
+    println!("Listening on http://{}", server.server_addr());
+    std::io::stdout().flush().unwrap();
+
+    let cache: Arc<Cache> =
+        Arc::new(NonZeroUsize::new(cli.cache_size).map(|x| Mutex::new(LruCache::new(x))));
+    // accepted requests, computing or waiting for a job
+    let pending = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel::<Request>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..cli.jobs {
+        let receiver = receiver.clone();
+        let cache = cache.clone();
+        let pending = pending.clone();
+        std::thread::spawn(move || loop {
+            let Ok(request) = receiver.lock().unwrap().recv() else {
+                break;
+            };
+            serve(request, &cache);
+            pending.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    let limit = cli.jobs + cli.queue;
+    for request in server.incoming_requests() {
+        if pending.fetch_add(1, Ordering::SeqCst) >= limit {
+            pending.fetch_sub(1, Ordering::SeqCst);
+            let error = ApiError {
+                status: 503,
+                message: "Too many requests, try again later".into(),
+            };
+            respond(request, Err(error), None);
+            continue;
+        }
+        sender.send(request).unwrap();
+    }
+}
+
+fn serve(mut request: Request, cache: &Cache) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    if !api::is_endpoint(&path) {
+        let error = ApiError {
+            status: 404,
+            message: format!("No endpoint {path}"),
+        };
+        return respond(request, Err(error), None);
+    }
+    if *request.method() != Method::Post {
+        let error = ApiError {
+            status: 405,
+            message: format!("{path} only accepts POST"),
+        };
+        return respond(request, Err(error), None);
+    }
+    let body = match read_body(&mut request) {
+        Ok(body) => body,
+        Err(error) => return respond(request, Err(error), None),
+    };
+
+    let key = cache.as_ref().and_then(|_| api::cache_key(&path, &body));
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Some(response) = cache.lock().unwrap().get(key).cloned() {
+            return respond(request, Ok(response), Some("hit"));
+        }
+    }
+    // a bug in the library must not take the job down
+    let result =
+        catch_unwind(AssertUnwindSafe(|| api::handle(&path, &body))).unwrap_or_else(|_| {
+            Err(ApiError {
+                status: 500,
+                message: "Internal error".into(),
+            })
+        });
+    if let (Some(cache), Some(key), Ok(response)) = (cache, key, &result) {
+        cache.lock().unwrap().put(key, response.clone());
+    }
+    respond(request, result, cache.as_ref().map(|_| "miss"));
+}
+
+fn read_body(request: &mut Request) -> Result<String, ApiError> {
+    let too_large = || ApiError {
+        status: 413,
+        message: format!("The body is larger than {MAX_BODY} bytes"),
+    };
+    if request.body_length().is_some_and(|x| x > MAX_BODY) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| ApiError {
+            status: 400,
+            message: format!("Cannot read the body: {e}"),
+        })?;
+    if body.len() > MAX_BODY {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn respond(request: Request, result: Result<String, ApiError>, cache: Option<&str>) {
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err(e) => (
+            e.status,
+            serde_json::json!({ "error": e.message }).to_string(),
+        ),
+    };
+    let mut response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+    if let Some(cache) = cache {
+        response.add_header(Header::from_bytes("X-Cache", cache).unwrap());
+    }
+    // the client may be gone already
+    let _ = request.respond(response);
+}
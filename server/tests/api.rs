@@ -0,0 +1,199 @@
+//! Runs `texas-odds-server` on a free localhost port and talks HTTP to it.
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Server {
+    fn start(args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_texas-odds-server"))
+            .args(["--addr", "127.0.0.1:0"])
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let addr = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap_or_else(|| panic!("unexpected output {line}"))
+            .to_string();
+        Server { child, addr }
+    }
+
+    fn connect(&self) -> TcpStream {
+        let stream = TcpStream::connect(&self.addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(60)))
+            .unwrap();
+        stream
+    }
+
+    fn request(&self, method: &str, path: &str, body: &str) -> Reply {
+        let mut stream = self.connect();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        Reply::read(stream)
+    }
+
+    fn post(&self, path: &str, body: Value) -> Reply {
+        self.request("POST", path, &body.to_string())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct Reply {
+    status: u16,
+    head: String,
+    body: Value,
+}
+
+impl Reply {
+    fn read(mut stream: TcpStream) -> Self {
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        let (head, body) = text.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        Reply {
+            status,
+            head: head.to_string(),
+            body: serde_json::from_str(body).unwrap(),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+}
+
+#[test]
+fn endpoints() {
+    let server = Server::start(&[]);
+
+    let reply = server.post(
+        "/odds",
+        json!({"hole_cards": ["hA", "dA"], "community_cards": ["c2", "d7", "sQ", "s3", "h9"]}),
+    );
+    assert_eq!(reply.status, 200, "{}", reply.body);
+    assert_eq!(reply.header("Content-Type"), Some("application/json"));
+    assert_eq!(reply.body["n_players"], 2);
+    assert!(reply.body["win"].as_f64().unwrap() > 0.8);
+    assert_eq!(reply.body["hand_rate"]["Pair"], 1.0);
+
+    let reply = server.post(
+        "/eval",
+        json!({"cards": ["hK", "dK", "cK", "h3", "d3", "s9"]}),
+    );
+    assert_eq!(reply.status, 200, "{}", reply.body);
+    assert_eq!(reply.body["hand_type"], "FullHouse");
+    assert_eq!(reply.body["cards"].as_array().unwrap().len(), 5);
+
+    let reply = server.post(
+        "/compare",
+        json!({"hands": [["hA", "dA"], ["sK", "cK"]], "community_cards": ["h2", "d7", "sQ", "s3", "h9"]}),
+    );
+    assert_eq!(reply.status, 200, "{}", reply.body);
+    assert_eq!(reply.body["equity"], json!([1.0, 0.0]));
+    assert_eq!(reply.body["hands"][1]["hand_type"], "Pair");
+
+    let body = json!({"hole_cards": ["hA", "dA"], "range": "KK", "iterations": 2000, "seed": 1});
+    let reply = server.post("/range", body.clone());
+    assert_eq!(reply.status, 200, "{}", reply.body);
+    assert_eq!(reply.body["combos"], 6);
+    assert!(reply.body["equity"].as_f64().unwrap() > 0.7);
+    assert_eq!(server.post("/range", body).body, reply.body);
+}
+
+#[test]
+fn errors() {
+    let server = Server::start(&[]);
+
+    let reply = server.post("/eval", json!({"cards": ["hA", "x2"]}));
+    assert_eq!(reply.status, 400);
+    assert_eq!(reply.body, json!({"error": "Invalid card x2"}));
+    assert_eq!(server.request("POST", "/eval", "{").status, 400);
+    assert_eq!(server.request("POST", "/nope", "{}").status, 404);
+    assert_eq!(server.request("GET", "/odds", "").status, 405);
+
+    let cards: Vec<String> = (0..20_000).map(|_| "hA".to_string()).collect();
+    let reply = server.post("/eval", json!({ "cards": cards }));
+    assert_eq!(reply.status, 413);
+}
+
+#[test]
+fn cache() {
+    let server = Server::start(&[]);
+    let reply = server.request(
+        "POST",
+        "/eval",
+        r#"{"cards": ["h2", "h7", "h9", "hJ", "hA"]}"#,
+    );
+    assert_eq!(reply.header("X-Cache"), Some("miss"));
+    // the same request written differently
+    let again = server.request("POST", "/eval", r#"{ "cards":["h2","h7","h9","hJ","hA"] }"#);
+    assert_eq!(again.header("X-Cache"), Some("hit"));
+    assert_eq!(again.body, reply.body);
+    // errors are not cached
+    let error = server.post("/eval", json!({"cards": []}));
+    assert_eq!(error.header("X-Cache"), Some("miss"));
+    let error = server.post("/eval", json!({"cards": []}));
+    assert_eq!(error.header("X-Cache"), Some("miss"));
+
+    let server = Server::start(&["--cache-size", "0"]);
+    let reply = server.post("/eval", json!({"cards": ["h2", "h7", "h9", "hJ", "hA"]}));
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.header("X-Cache"), None);
+}
+
+#[test]
+fn concurrency_limit() {
+    let server = Server::start(&["--jobs", "1", "--queue", "0"]);
+    // keep the only job busy waiting for the rest of the body, tiny_http reads the small bodies
+    // before handing over the request
+    let body = format!(
+        r#"{{"cards": ["h2", "h7", "h9", "hJ", "hA"]}}{}"#,
+        " ".repeat(2048)
+    );
+    let mut busy = server.connect();
+    write!(
+        busy,
+        "POST /eval HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )
+    .unwrap();
+    sleep(Duration::from_millis(300));
+
+    let reply = server.post("/eval", json!({"cards": ["h2", "h7", "h9", "hJ", "hA"]}));
+    assert_eq!(reply.status, 503);
+
+    busy.write_all(body.as_bytes()).unwrap();
+    let reply = Reply::read(busy);
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.body["hand_type"], "Flush");
+}
@@ -1,10 +1,5 @@
-import { Stage } from "texas-odds";
-
-export interface OddsOrigin {
-  win: number;
-  tie: number;
-  hand_type_rates: Record<string, number>;
-}
+import { evaluate, OddsJob, Stage } from "texas-odds";
+import type { HandEval, Odds as OddsOrigin, WinRate } from "texas-odds";
 
 export interface Odds {
   win: number;
@@ -12,6 +7,7 @@ export interface Odds {
   hand_type_rates: Record<string, string>;
 }
 
+/** Throws an `Error` with a readable message when the input is invalid */
 export function calc(
   players: number,
   holeCardsStr: string,
@@ -19,23 +15,87 @@ export function calc(
 ): Odds {
   const holeCards = holeCardsStr.split(" ");
   const stage = new Stage(players, holeCards[0], holeCards[1], community_card);
-  const odds = stage.odds() as any as OddsOrigin;
-  console.log(odds);
+  try {
+    return format(stage.odds());
+  } finally {
+    stage.free();
+  }
+}
+
+/**
+ * Runs the calculation a few milliseconds at a time between the browser tasks, so the page keeps
+ * responding. `onProgress` gets the odds so far; the promise resolves to `undefined` once `signal`
+ * is aborted.
+ */
+export async function calcChunked(
+  players: number,
+  holeCardsStr: string,
+  community_card: string,
+  onProgress: (done: number, odds: Odds) => void,
+  signal?: AbortSignal,
+): Promise<Odds | undefined> {
+  const holeCards = holeCardsStr.split(" ");
+  const job = new OddsJob(players, holeCards[0], holeCards[1], community_card);
+  try {
+    while (!job.step(16)) {
+      onProgress(job.progress, format(job.odds()));
+      await new Promise((resolve) => setTimeout(resolve));
+      if (signal?.aborted) {
+        return undefined;
+      }
+    }
+    return format(job.odds());
+  } finally {
+    job.free();
+  }
+}
+
+/** The best hand made of the hole and community cards, once there are at least 5 of them */
+export function describe(
+  holeCardsStr: string,
+  community_card: string,
+): HandEval | undefined {
+  const cards = `${holeCardsStr} ${community_card}`.trim();
+  if (cards.split(/\s+/).length < 5) {
+    return undefined;
+  }
+  return evaluate(cards);
+}
+
+/** The distribution of the win rate against one random hand */
+export function winRate(
+  holeCardsStr: string,
+  community_card: string,
+): WinRate {
+  const holeCards = holeCardsStr.split(" ");
+  const stage = new Stage(2, holeCards[0], holeCards[1], community_card);
+  try {
+    return stage.winRate();
+  } finally {
+    stage.free();
+  }
+}
+
+export function formatRate(rate: number): string {
+  rate *= 100;
+  if (rate === 0) {
+    return `0`;
+  } else if (rate > 0.01) {
+    return `${rate.toFixed(2)}%`;
+  } else {
+    return `${rate.toExponential(4)}%`;
+  }
+}
+
+function format(odds: OddsOrigin): Odds {
   const output: Odds = {
     win: odds.win,
-    tie: odds.win,
+    tie: odds.tie,
     hand_type_rates: {},
   };
 
-  for (const key in odds.hand_type_rates) {
-    const rate = odds.hand_type_rates[key] * 100;
-    if (rate === 0) {
-      output.hand_type_rates[key] = `0`;
-    } else if (rate > 0.01) {
-      output.hand_type_rates[key] = `${rate.toFixed(2)}%`;
-    } else {
-      output.hand_type_rates[key] = `${rate.toExponential(4)}%`;
-    }
+  for (const [key, rate] of Object.entries(odds.hand_type_rates)) {
+    output.hand_type_rates[key] = formatRate(rate);
   }
   return output;
 }
@@ -0,0 +1,19 @@
+use std::env;
+
+use texas_odds::sim::{AlwaysCall, EquityThreshold, RandomBot, Tournament};
+
+/// cargo run -r --example self_play -- [N_HANDS] [SEED]
+fn main() {
+    let mut args = env::args().skip(1);
+    let n_hands = args.next().map(|x| x.parse().unwrap()).unwrap_or(100);
+    let seed = args.next().map(|x| x.parse().unwrap()).unwrap_or(0);
+    let mut tournament = Tournament::new(
+        vec![
+            Box::new(EquityThreshold::default()),
+            Box::new(AlwaysCall),
+            Box::new(RandomBot::new(seed)),
+        ],
+        seed,
+    );
+    println!("{}", tournament.run(n_hands));
+}
@@ -0,0 +1,367 @@
+//! Board texture: pairing, suits, connectedness and height of the community cards.
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+use core::fmt::Display;
+
+use crate::texas::{iter_all_cards, Card, CardNum, Suit};
+
+const SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Club, Suit::Spade];
+
+/// 公开池的对子情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    TwoPaired,
+    Trips,
+    FullHouse,
+    Quads,
+}
+
+/// 公开池的花色情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Suits {
+    /// No two cards of the same suit
+    Rainbow,
+    /// Two cards of a suit at most
+    TwoTone,
+    /// Three or four cards of a suit, but not all of them
+    FlushBoard,
+    /// Every card of the same suit
+    Monotone,
+}
+
+/// The highest card of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Height {
+    /// 2 to 6
+    Low,
+    /// 7 to 9
+    Middle,
+    /// T to K
+    Broadway,
+    AceHigh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardTexture {
+    pub pairing: Pairing,
+    pub suits: Suits,
+    pub height: Height,
+    /// The most cards of one suit
+    pub max_suit: usize,
+    /// The longest run of consecutive ranks, an ace also counts below the two
+    pub longest_run: usize,
+    /// A player can hold a flush
+    pub flush_possible: bool,
+    /// A player can hold four cards of a flush, only before the river
+    pub flush_draw_possible: bool,
+    /// A player can hold a straight
+    pub straight_possible: bool,
+    /// A player can hold four cards of a straight, only before the river
+    pub straight_draw_possible: bool,
+    /// Distinct hole card ranks, like `T9` or `88`, that make a straight
+    pub straight_combos: usize,
+}
+
+/// How the texture changes on the next street, in number of unseen cards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NextStreet {
+    /// Cards left in the deck
+    pub cards: usize,
+    /// Cards that pair the board further
+    pub pair: usize,
+    /// Cards that make a flush possible, or add to the suit with the most cards
+    pub flush: usize,
+    /// Cards that make a straight possible
+    pub straight: usize,
+    /// Cards that add straight combos
+    pub more_straights: usize,
+    /// Cards higher than the board
+    pub overcards: usize,
+    /// Every unseen card with the texture it leads to
+    pub textures: Vec<(Card, BoardTexture)>,
+}
+
+/// 2 to 14, the ace is 14
+fn value(num: CardNum) -> u8 {
+    num as u8 + 2
+}
+
+fn assert_board(pub_cards: &[Card]) {
+    assert!(
+        pub_cards.len() >= 3 && pub_cards.len() <= 5,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+}
+
+fn pairing(pub_cards: &[Card]) -> Pairing {
+    let mut counts = Vec::new();
+    let mut nums: Vec<CardNum> = pub_cards.iter().map(|x| x.num).collect();
+    nums.sort_unstable();
+    nums.dedup();
+    for num in nums {
+        counts.push(pub_cards.iter().filter(|x| x.num == num).count());
+    }
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    match counts.as_slice() {
+        [4, ..] => Pairing::Quads,
+        [3, 2, ..] => Pairing::FullHouse,
+        [3, ..] => Pairing::Trips,
+        [2, 2, ..] => Pairing::TwoPaired,
+        [2, ..] => Pairing::Paired,
+        _ => Pairing::Unpaired,
+    }
+}
+
+/// Longest chain of `CardNum::is_next`, the ace can start a wheel
+fn longest_run(pub_cards: &[Card]) -> usize {
+    let mut nums: Vec<CardNum> = pub_cards.iter().map(|x| x.num).collect();
+    nums.sort_unstable();
+    nums.dedup();
+    if nums.last() == Some(&CardNum::Ace) {
+        nums.insert(0, CardNum::Ace);
+    }
+    let mut longest = 1;
+    let mut run = 1;
+    for pair in nums.windows(2) {
+        if pair[0].is_next(&pair[1]) {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 1;
+        }
+    }
+    longest
+}
+
+/// Hole card ranks that make a straight with the board
+fn straight_holdings(pub_cards: &[Card]) -> BTreeSet<(u8, u8)> {
+    let on_board = |v: u8| {
+        pub_cards
+            .iter()
+            .any(|x| value(x.num) == v || (v == 1 && x.num == CardNum::Ace))
+    };
+    let mut holdings = BTreeSet::new();
+    // from the wheel A-5 to the broadway T-A
+    for low in 1..=10u8 {
+        let missing: Vec<u8> = (low..low + 5)
+            .filter(|v| !on_board(*v))
+            .map(|v| if v == 1 { 14 } else { v })
+            .collect();
+        match missing.as_slice() {
+            [] => {
+                for a in 2..=14 {
+                    for b in a..=14 {
+                        holdings.insert((a, b));
+                    }
+                }
+            }
+            [m] => {
+                for x in 2..=14 {
+                    holdings.insert(((*m).min(x), (*m).max(x)));
+                }
+            }
+            [a, b] => {
+                holdings.insert(((*a).min(*b), (*a).max(*b)));
+            }
+            _ => {}
+        }
+    }
+    holdings
+}
+
+/// Some window of five ranks has two cards of the board, so two hole cards make four of a straight
+fn straight_draw(pub_cards: &[Card]) -> bool {
+    (1..=10u8).any(|low| {
+        (low..low + 5)
+            .filter(|v| {
+                pub_cards
+                    .iter()
+                    .any(|x| value(x.num) == *v || (*v == 1 && x.num == CardNum::Ace))
+            })
+            .count()
+            >= 2
+    })
+}
+
+impl BoardTexture {
+    /// The texture of a flop, turn or river
+    pub fn new(pub_cards: &[Card]) -> Self {
+        assert_board(pub_cards);
+        let max_suit = SUITS
+            .iter()
+            .map(|suit| pub_cards.iter().filter(|x| x.suit == *suit).count())
+            .max()
+            .unwrap();
+        let suits = match max_suit {
+            1 => Suits::Rainbow,
+            2 => Suits::TwoTone,
+            x if x == pub_cards.len() => Suits::Monotone,
+            _ => Suits::FlushBoard,
+        };
+        let height = match pub_cards.iter().map(|x| x.num).max().unwrap() {
+            CardNum::Ace => Height::AceHigh,
+            x if x >= CardNum::Ten => Height::Broadway,
+            x if x >= CardNum::Seven => Height::Middle,
+            _ => Height::Low,
+        };
+        let before_river = pub_cards.len() < 5;
+        let straight_combos = straight_holdings(pub_cards).len();
+
+        BoardTexture {
+            pairing: pairing(pub_cards),
+            suits,
+            height,
+            max_suit,
+            longest_run: longest_run(pub_cards),
+            flush_possible: max_suit >= 3,
+            flush_draw_possible: before_river && max_suit >= 2,
+            straight_possible: straight_combos > 0,
+            straight_draw_possible: before_river && straight_draw(pub_cards),
+            straight_combos,
+        }
+    }
+
+    /// Short tags for the texture, e.g. `paired, two-tone, connected`
+    pub fn tags(&self) -> Vec<&'static str> {
+        let mut tags = vec![
+            match self.pairing {
+                Pairing::Unpaired => "unpaired",
+                Pairing::Paired => "paired",
+                Pairing::TwoPaired => "two paired",
+                Pairing::Trips => "trips",
+                Pairing::FullHouse => "full house",
+                Pairing::Quads => "quads",
+            },
+            match self.suits {
+                Suits::Rainbow => "rainbow",
+                Suits::TwoTone => "two-tone",
+                Suits::FlushBoard => "flush possible",
+                Suits::Monotone => "monotone",
+            },
+            match self.height {
+                Height::Low => "low",
+                Height::Middle => "middle",
+                Height::Broadway => "broadway",
+                Height::AceHigh => "ace high",
+            },
+        ];
+        if self.longest_run >= 3 {
+            tags.push("connected");
+        }
+        if self.straight_possible {
+            tags.push("straight possible");
+        }
+        if self.flush_draw_possible && !self.flush_possible {
+            tags.push("flush draw");
+        }
+        if self.straight_draw_possible && !self.straight_possible {
+            tags.push("straight draw");
+        }
+        tags
+    }
+}
+
+impl Display for BoardTexture {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.tags().join(", "))
+    }
+}
+
+/// The texture of every possible next card of a flop or turn
+pub fn next_street(pub_cards: &[Card]) -> NextStreet {
+    assert!(
+        pub_cards.len() == 3 || pub_cards.len() == 4,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+    let now = BoardTexture::new(pub_cards);
+    let top = pub_cards.iter().map(|x| x.num).max().unwrap();
+    let mut next = NextStreet::default();
+    for card in iter_all_cards().filter(|x| !pub_cards.contains(x)) {
+        let mut cards = pub_cards.to_vec();
+        cards.push(card);
+        let texture = BoardTexture::new(&cards);
+        next.cards += 1;
+        next.pair += (texture.pairing > now.pairing) as usize;
+        next.flush += (texture.flush_possible && texture.max_suit > now.max_suit) as usize;
+        next.straight += (texture.straight_possible && !now.straight_possible) as usize;
+        next.more_straights += (texture.straight_combos > now.straight_combos) as usize;
+        next.overcards += (card.num > top) as usize;
+        next.textures.push((card, texture));
+    }
+    next
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::ToString, vec::Vec};
+
+    use super::{next_street, BoardTexture, Height, Pairing, Suits};
+    use crate::texas::Card;
+
+    fn board(s: &str) -> Vec<Card> {
+        s.split(' ').map(Card::from).collect()
+    }
+
+    #[test]
+    fn texture() {
+        let dry = BoardTexture::new(&board("hK d7 c2"));
+        assert_eq!(dry.pairing, Pairing::Unpaired);
+        assert_eq!(dry.suits, Suits::Rainbow);
+        assert_eq!(dry.height, Height::Broadway);
+        assert!(!dry.straight_possible);
+        assert!(!dry.flush_draw_possible);
+        // no five ranks hold two of the cards
+        assert!(!dry.straight_draw_possible);
+        assert_eq!(dry.to_string(), "unpaired, rainbow, broadway");
+        assert!(BoardTexture::new(&board("hK dJ c2")).straight_draw_possible);
+
+        let wet = BoardTexture::new(&board("h9 h8 h7"));
+        assert_eq!(wet.suits, Suits::Monotone);
+        assert_eq!(wet.longest_run, 3);
+        assert!(wet.flush_possible);
+        // JT, T6, 65
+        assert_eq!(wet.straight_combos, 3);
+
+        // the ace plays low
+        let wheel = BoardTexture::new(&board("hA d2 c3 s3"));
+        assert_eq!(wheel.longest_run, 3);
+        assert_eq!(wheel.pairing, Pairing::Paired);
+        assert_eq!(wheel.height, Height::AceHigh);
+        assert_eq!(wheel.straight_combos, 1);
+
+        let river = BoardTexture::new(&board("hT dJ cQ sK h2"));
+        assert!(!river.straight_draw_possible);
+        // any ace or nine, 9A counted once
+        assert_eq!(river.straight_combos, 25);
+
+        assert_eq!(
+            BoardTexture::new(&board("hQ dQ cQ h5 d5")).pairing,
+            Pairing::FullHouse
+        );
+    }
+
+    #[test]
+    fn next() {
+        let next = next_street(&board("hK h7 c2"));
+        assert_eq!(next.cards, 49);
+        assert_eq!(next.pair, 9);
+        assert_eq!(next.flush, 11);
+        assert_eq!(next.straight, 0);
+        assert_eq!(next.overcards, 4);
+        assert_eq!(next.textures.len(), 49);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid pub_cards length 5")]
+    fn no_next_street_on_the_river() {
+        next_street(&board("hT dJ cQ sK h2"));
+    }
+}
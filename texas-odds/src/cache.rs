@@ -0,0 +1,351 @@
+//! Memoized odds of `Scenario`s, in memory and optionally in a cache file.
+//!
+//! The odds don't change when the suits are renamed or the cards reordered, so the scenarios are
+//! keyed by a canonical form: `hA dA` on `c2 d7 sQ` shares its entry with `sA cA` on `hQ c7 d2`.
+//!
+//! The cache file has one entry per line, the oldest first: the key (hole cards, community cards,
+//! dead cards and players), a tab, then the odds.
+//!
+//! > hA dA | c2 h7 sQ |  | 2 <TAB> win=0.68 tie=0.002 Pair=0.53 TwoPair=0.31
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    odds::Odds,
+    scenario::Scenario,
+    texas::{Card, HandType, Suit},
+};
+
+/// Entries kept by the CLI and the wasm build
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// A scenario up to the suit names and the order of the cards
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalKey {
+    /// `num * 4 + suit` of each card, sorted
+    hole: [u8; 2],
+    board: Vec<u8>,
+    dead: Vec<u8>,
+    n_players: usize,
+}
+
+fn suit_index(suit: Suit) -> u8 {
+    match suit {
+        Suit::Heart => 0,
+        Suit::Diamond => 1,
+        Suit::Club => 2,
+        Suit::Spade => 3,
+    }
+}
+
+/// The 24 ways to rename the suits
+fn suit_permutations() -> Vec<[u8; 4]> {
+    let mut permutations = Vec::with_capacity(24);
+    for a in 0..4 {
+        for b in (0..4).filter(|&x| x != a) {
+            for c in (0..4).filter(|&x| x != a && x != b) {
+                permutations.push([a, b, c, 6 - a - b - c]);
+            }
+        }
+    }
+    permutations
+}
+
+impl CanonicalKey {
+    pub fn new(
+        hole_cards: [Card; 2],
+        community_cards: &[Card],
+        dead_cards: &[Card],
+        n_players: usize,
+    ) -> Self {
+        let mut best: Option<CanonicalKey> = None;
+        for permutation in suit_permutations() {
+            let code =
+                |card: &Card| card.num as u8 * 4 + permutation[suit_index(card.suit) as usize];
+            let codes = |cards: &[Card]| {
+                let mut codes: Vec<u8> = cards.iter().map(code).collect();
+                codes.sort_unstable();
+                codes
+            };
+            let mut hole = hole_cards.map(|x| code(&x));
+            hole.sort_unstable();
+            let key = CanonicalKey {
+                hole,
+                board: codes(community_cards),
+                dead: codes(dead_cards),
+                n_players,
+            };
+            if best.as_ref().map_or(true, |x| key < *x) {
+                best = Some(key);
+            }
+        }
+        best.unwrap()
+    }
+}
+
+impl From<&Scenario> for CanonicalKey {
+    fn from(value: &Scenario) -> Self {
+        CanonicalKey::new(
+            value.hole_cards,
+            &value.community_cards,
+            &value.dead_cards,
+            value.n_players,
+        )
+    }
+}
+
+/// `h2` to `sA`, like the card arguments of the CLI
+fn write_codes(f: &mut std::fmt::Formatter<'_>, codes: &[u8]) -> std::fmt::Result {
+    for (i, code) in codes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        let suit = b"hdcs"[(code % 4) as usize] as char;
+        let num = b"23456789TJQKA"[(code / 4) as usize] as char;
+        write!(f, "{suit}{num}")?;
+    }
+    Ok(())
+}
+
+impl Display for CanonicalKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_codes(f, &self.hole)?;
+        f.write_str(" | ")?;
+        write_codes(f, &self.board)?;
+        f.write_str(" | ")?;
+        write_codes(f, &self.dead)?;
+        write!(f, " | {}", self.n_players)
+    }
+}
+
+/// Reads a line of the cache file, the key is canonicalized again in case it was edited
+fn parse_entry(line: &str) -> Option<(CanonicalKey, Odds)> {
+    let (key, odds) = line.split_once('\t')?;
+    let fields: Vec<&str> = key.split('|').map(str::trim).collect();
+    let [hole, board, dead, n_players] = fields[..] else {
+        return None;
+    };
+    let cards = |text: &str| -> Option<Vec<Card>> {
+        text.split_whitespace().map(|x| x.parse().ok()).collect()
+    };
+    let hole: [Card; 2] = cards(hole)?.try_into().ok()?;
+    let key = CanonicalKey::new(hole, &cards(board)?, &cards(dead)?, n_players.parse().ok()?);
+
+    let mut win = None;
+    let mut tie = None;
+    let mut hand_rate = BTreeMap::new();
+    for field in odds.split_whitespace() {
+        let (name, value) = field.split_once('=')?;
+        let value: f64 = value.parse().ok()?;
+        match name {
+            "win" => win = Some(value),
+            "tie" => tie = Some(value),
+            _ => {
+                let hand_type = HandType::ALL.into_iter().find(|x| x.to_string() == name)?;
+                hand_rate.insert(hand_type, value);
+            }
+        }
+    }
+    Some((
+        key,
+        Odds {
+            win: win?,
+            tie: tie?,
+            hand_rate,
+        },
+    ))
+}
+
+/// Odds by `CanonicalKey`, dropping the least recently used entry beyond `capacity`
+#[derive(Debug)]
+pub struct OddsCache {
+    capacity: usize,
+    /// The odds and the tick of their last use
+    entries: HashMap<CanonicalKey, (Odds, u64)>,
+    /// Keys by the tick of their last use
+    order: BTreeMap<u64, CanonicalKey>,
+    tick: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl OddsCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn get(&mut self, key: &CanonicalKey) -> Option<Odds> {
+        self.tick += 1;
+        let Some((odds, tick)) = self.entries.get_mut(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        let old = std::mem::replace(tick, self.tick);
+        let odds = odds.clone();
+        self.order.remove(&old);
+        self.order.insert(self.tick, key.clone());
+        Some(odds)
+    }
+
+    pub fn insert(&mut self, key: CanonicalKey, odds: Odds) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, old)) = self.entries.insert(key.clone(), (odds, self.tick)) {
+            self.order.remove(&old);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            let (_, oldest) = self.order.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// The odds of `scenario`, computed only if no equivalent scenario is cached
+    pub fn odds(&mut self, scenario: &Scenario) -> Odds {
+        let key = CanonicalKey::from(scenario);
+        if let Some(odds) = self.get(&key) {
+            return odds;
+        }
+        let odds = scenario.odds();
+        self.insert(key, odds.clone());
+        odds
+    }
+
+    /// Adds the entries of a cache file, invalid lines are skipped. A missing file is empty.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            if let Some((key, odds)) = parse_entry(&line?) {
+                self.insert(key, odds);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes every entry, the least recently used first so `load` keeps their order
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut out = BufWriter::new(std::fs::File::create(path)?);
+        for key in self.order.values() {
+            let (odds, _) = &self.entries[key];
+            write!(out, "{key}\twin={} tie={}", odds.win, odds.tie)?;
+            for (hand_type, rate) in &odds.hand_rate {
+                write!(out, " {hand_type}={rate}")?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+}
+
+impl Default for OddsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CanonicalKey, OddsCache};
+    use crate::{scenario::Scenario, texas::Card};
+
+    fn key(line: &str) -> CanonicalKey {
+        CanonicalKey::from(&line.parse::<Scenario>().unwrap())
+    }
+
+    #[test]
+    fn canonical() {
+        assert_eq!(key("hA dA c2 d7 sQ"), key("sA cA hQ c7 d2"));
+        assert_eq!(key("hA dA c2 d7 sQ"), key("dA hA d7 sQ c2"));
+        assert_eq!(key("hA dA c2 d7 sQ n=3"), key("sA cA hQ c7 d2 n=3"));
+        assert_ne!(key("hA dA c2 d7 sQ"), key("hA dA c2 d7 sQ n=3"));
+        // suited and offsuit
+        assert_ne!(key("hA hK"), key("hA dK"));
+        assert_eq!(key("hA hK"), key("sA sK"));
+        assert_ne!(key("hA hK dead=h2"), key("hA hK dead=d2"));
+        assert_eq!(key("hA hK h2 d7 sQ").to_string(), "hK hA | h2 d7 cQ |  | 2");
+    }
+
+    #[test]
+    fn lru() {
+        let mut cache = OddsCache::new(2);
+        let odds = |win| crate::odds::Odds {
+            win,
+            tie: 0.,
+            hand_rate: Default::default(),
+        };
+        cache.insert(key("hA dA"), odds(0.1));
+        cache.insert(key("hK dK"), odds(0.2));
+        assert!(cache.get(&key("sA cA")).is_some());
+        cache.insert(key("hQ dQ"), odds(0.3));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key("hK dK")).is_none());
+        assert_eq!(cache.get(&key("hA dA")).unwrap().win, 0.1);
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+    }
+
+    #[test]
+    fn file() {
+        let path = std::env::temp_dir().join(format!("texas-odds-cache-{}", std::process::id()));
+        let mut cache = OddsCache::default();
+        let flop: Scenario = "hA dA c2 d7 sQ".parse().unwrap();
+        let river: Scenario = "h2 d3 s4 c5 d13 hA sA n=3".parse().unwrap();
+        let odds = cache.odds(&flop);
+        cache.odds(&river);
+        cache.save(&path).unwrap();
+
+        let mut loaded = OddsCache::default();
+        loaded.load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        let same: Scenario = "sA cA hQ c7 d2".parse().unwrap();
+        let cached = loaded.odds(&same);
+        assert_eq!(loaded.hits(), 1);
+        assert_eq!(cached.win, odds.win);
+        assert_eq!(cached.hand_rate, odds.hand_rate);
+
+        let mut empty = OddsCache::default();
+        empty.load("/nonexistent/texas-odds-cache").unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(
+            super::parse_entry("hA dA | | | 2\twin=0.5 tie=0.1 Pair=1")
+                .unwrap()
+                .0,
+            CanonicalKey::new([Card::from("sA"), Card::from("cA")], &[], &[], 2)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! The 13x13 preflop starting hand chart: pairs on the diagonal, suited hands above it, offsuit below.
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::{Display, Write};
+
+use crate::{
+    odds::{fill_5_and_get_all_hands, odds_with_n_players},
+    texas::{Card, CardNum, Suit, Variant},
+};
+
+/// From the top left corner of the chart
+const RANKS: [CardNum; 13] = [
+    CardNum::Ace,
+    CardNum::King,
+    CardNum::Queen,
+    CardNum::Jack,
+    CardNum::Ten,
+    CardNum::Nine,
+    CardNum::Eight,
+    CardNum::Seven,
+    CardNum::Six,
+    CardNum::Five,
+    CardNum::Four,
+    CardNum::Three,
+    CardNum::Two,
+];
+
+const CELL_SIZE: usize = 48;
+
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub n_players: usize,
+    /// `equity[row][col]`, a tie counts as half a win
+    pub equity: [[f64; 13]; 13],
+}
+
+fn rank_label(num: CardNum) -> String {
+    match num {
+        CardNum::Ten => "T".to_string(),
+        x => x.to_string(),
+    }
+}
+
+/// `AA`, `AKs` above the diagonal or `AKo` below it
+pub fn label(row: usize, col: usize) -> String {
+    let high = rank_label(RANKS[row.min(col)]);
+    let low = rank_label(RANKS[row.max(col)]);
+    match row.cmp(&col) {
+        core::cmp::Ordering::Equal => format!("{high}{low}"),
+        core::cmp::Ordering::Less => format!("{high}{low}s"),
+        core::cmp::Ordering::Greater => format!("{high}{low}o"),
+    }
+}
+
+/// One hand of the cell, all hands of a cell have the same preflop equity
+fn hole_cards(row: usize, col: usize) -> [Card; 2] {
+    let second_suit = if row < col { Suit::Heart } else { Suit::Spade };
+    [
+        Card {
+            suit: Suit::Heart,
+            num: RANKS[row.min(col)],
+        },
+        Card {
+            suit: second_suit,
+            num: RANKS[row.max(col)],
+        },
+    ]
+}
+
+/// Red for the weakest hand of the chart to green for the strongest
+fn color(t: f64) -> (u8, u8, u8) {
+    let t = t.clamp(0., 1.);
+    let r = if t < 0.5 { 230. } else { 230. * (1. - t) * 2. };
+    let g = if t < 0.5 { 200. * t * 2. } else { 200. };
+    (r as u8, g as u8, 80)
+}
+
+/// Equity of every starting hand against `n - 1` random hands, with the same approximation as
+/// `Stage::win_rate_with_n_players`
+pub fn preflop_chart(n: usize) -> Chart {
+    assert!(n >= 2, "n_players must be >= 2");
+    // the opponent hands don't depend on ours, enumerate them once for the whole chart
+    let all_hands = fill_5_and_get_all_hands(&[], &[], Variant::Standard);
+    let mut equity = [[0.; 13]; 13];
+    for (row, line) in equity.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            let my_hands = fill_5_and_get_all_hands(&hole_cards(row, col), &[], Variant::Standard);
+            let odds = odds_with_n_players(&my_hands, &all_hands, n);
+            *cell = odds.win + odds.tie / 2.;
+        }
+    }
+    Chart {
+        n_players: n,
+        equity,
+    }
+}
+
+impl Chart {
+    fn min_max(&self) -> (f64, f64) {
+        let all = self.equity.iter().flatten();
+        let min = all.clone().copied().fold(f64::INFINITY, f64::min);
+        let max = all.copied().fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    }
+
+    fn scale(&self, equity: f64) -> f64 {
+        let (min, max) = self.min_max();
+        if max > min {
+            (equity - min) / (max - min)
+        } else {
+            1.
+        }
+    }
+
+    /// The grid as text, with ANSI background colors if `colored`
+    pub fn to_table(&self, colored: bool) -> String {
+        let mut out = String::new();
+        for (row, line) in self.equity.iter().enumerate() {
+            for (col, equity) in line.iter().enumerate() {
+                let cell = format!("{:<3} {:>4.1}", label(row, col), equity * 100.);
+                if colored {
+                    let (r, g, b) = color(self.scale(*equity));
+                    write!(out, "\x1b[30;48;2;{r};{g};{b}m {cell} \x1b[0m").unwrap();
+                } else {
+                    write!(out, " {cell} ").unwrap();
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// `hand,equity` rows, from the top left corner of the grid
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("hand,equity\n");
+        for (row, line) in self.equity.iter().enumerate() {
+            for (col, equity) in line.iter().enumerate() {
+                writeln!(out, "{},{}", label(row, col), equity).unwrap();
+            }
+        }
+        out
+    }
+
+    pub fn to_svg(&self) -> String {
+        let size = CELL_SIZE * 13;
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}" font-family="sans-serif" text-anchor="middle">"#
+        )
+        .unwrap();
+        for (row, line) in self.equity.iter().enumerate() {
+            for (col, equity) in line.iter().enumerate() {
+                let (x, y) = (col * CELL_SIZE, row * CELL_SIZE);
+                let (r, g, b) = color(self.scale(*equity));
+                let center = x + CELL_SIZE / 2;
+                writeln!(
+                    out,
+                    r#"<rect x="{x}" y="{y}" width="{CELL_SIZE}" height="{CELL_SIZE}" fill="rgb({r},{g},{b})" stroke="white"/>"#
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    r#"<text x="{center}" y="{}" font-size="13" font-weight="bold">{}</text>"#,
+                    y + 20,
+                    label(row, col)
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    r#"<text x="{center}" y="{}" font-size="11">{:.1}</text>"#,
+                    y + 37,
+                    equity * 100.
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+impl Display for Chart {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{} Players", self.n_players)?;
+        f.write_str(&self.to_table(false))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::string::ToString;
+
+    use super::{label, preflop_chart};
+
+    #[test]
+    fn labels() {
+        assert_eq!(label(0, 0), "AA");
+        assert_eq!(label(0, 1), "AKs");
+        assert_eq!(label(1, 0), "AKo");
+        assert_eq!(label(4, 12), "T2s");
+        assert_eq!(label(12, 12), "22");
+    }
+
+    #[test]
+    fn chart() {
+        let chart = preflop_chart(2);
+        let equity = chart.equity;
+        let max = equity.iter().flatten().copied().fold(0., f64::max);
+        assert_eq!(equity[0][0], max);
+        // suited beats offsuit
+        for (row, line) in equity.iter().enumerate() {
+            for (col, suited) in line.iter().enumerate().skip(row + 1) {
+                assert!(*suited > equity[col][row], "{}", label(row, col));
+            }
+        }
+        assert!(equity[0][0] > 0.8);
+        assert!(equity[12][12] > equity[7][12]);
+
+        let csv = chart.to_csv();
+        assert_eq!(csv.lines().count(), 170);
+        assert!(csv.starts_with("hand,equity\nAA,"));
+        let svg = chart.to_svg();
+        assert_eq!(svg.matches("<rect").count(), 169);
+        assert_eq!(chart.to_string().lines().count(), 14);
+    }
+}
@@ -0,0 +1,627 @@
+//! Plain-text hand history import (PokerStars and GGPoker) and equity replay.
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+};
+
+use crate::{
+    odds::{showdown_equity, Stage},
+    sim::Street,
+    texas::{parse_card, Card},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Site {
+    PokerStars,
+    GGPoker,
+}
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub seat: usize,
+    pub name: String,
+    pub stack: f64,
+    /// Known when dealt to the hero or shown/mucked at showdown
+    pub hole_cards: Option<[Card; 2]>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ActionKind {
+    PostSmallBlind(f64),
+    PostBigBlind(f64),
+    PostAnte(f64),
+    Fold,
+    Check,
+    Call(f64),
+    Bet(f64),
+    /// Raise to the given total amount on this street
+    Raise(f64),
+    /// Part of a bet that nobody called, given back to the player
+    Uncalled(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct HandAction {
+    pub street: Street,
+    pub player: String,
+    pub kind: ActionKind,
+    pub all_in: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct HandHistory {
+    pub site: Site,
+    pub id: String,
+    pub small_blind: f64,
+    pub big_blind: f64,
+    pub button_seat: usize,
+    pub players: Vec<Player>,
+    /// The player the hole cards were dealt to
+    pub hero: Option<String>,
+    pub board: Vec<Card>,
+    pub actions: Vec<HandAction>,
+    /// Amount collected from the pot by each winner
+    pub collected: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses every hand found in `text`. Hands start with their `PokerStars Hand #` or `Poker Hand #` header.
+pub fn parse(text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    let mut hands = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim_end();
+        if header_site(line).is_some() {
+            if let Some((start, lines)) = current.take() {
+                hands.push(parse_hand(start, &lines)?);
+            }
+            current = Some((i + 1, Vec::new()));
+        }
+        if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    if let Some((start, lines)) = current {
+        hands.push(parse_hand(start, &lines)?);
+    }
+
+    Ok(hands)
+}
+
+fn header_site(line: &str) -> Option<Site> {
+    if line.starts_with("PokerStars Hand #") {
+        Some(Site::PokerStars)
+    } else if line.starts_with("Poker Hand #") {
+        Some(Site::GGPoker)
+    } else {
+        None
+    }
+}
+
+fn parse_hand(start: usize, lines: &[&str]) -> Result<HandHistory, ParseError> {
+    let err = |i: usize, message: String| ParseError {
+        line: start + i,
+        message,
+    };
+    let header = lines[0];
+    let site = header_site(header).unwrap();
+    let id = header
+        .split('#')
+        .nth(1)
+        .and_then(|x| x.split(':').next())
+        .ok_or_else(|| err(0, "missing hand id".into()))?
+        .trim()
+        .to_string();
+    let (small_blind, big_blind) =
+        parse_stakes(header).ok_or_else(|| err(0, format!("missing stakes in {header:?}")))?;
+
+    let mut hand = HandHistory {
+        site,
+        id,
+        small_blind,
+        big_blind,
+        button_seat: 0,
+        players: Vec::new(),
+        hero: None,
+        board: Vec::new(),
+        actions: Vec::new(),
+        collected: Vec::new(),
+    };
+    let mut street = Street::Preflop;
+    let mut in_summary = false;
+    for (i, line) in lines.iter().enumerate().skip(1) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix("*** ") {
+            let (name, cards) = section.split_once(" ***").unwrap_or((section, ""));
+            match name {
+                "FLOP" | "TURN" | "RIVER" => {
+                    street = match name {
+                        "FLOP" => Street::Flop,
+                        "TURN" => Street::Turn,
+                        _ => Street::River,
+                    };
+                    hand.board = parse_bracketed_cards(cards)
+                        .ok_or_else(|| err(i, format!("invalid board {cards:?}")))?;
+                }
+                "SUMMARY" => in_summary = true,
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Table ") {
+            if let Some(seat) = rest
+                .split("Seat #")
+                .nth(1)
+                .and_then(|x| x.split_whitespace().next())
+            {
+                hand.button_seat = seat
+                    .parse()
+                    .map_err(|_| err(i, format!("invalid button seat {seat:?}")))?;
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Seat ") {
+            let (seat, rest) = rest
+                .split_once(": ")
+                .ok_or_else(|| err(i, format!("invalid seat line {line:?}")))?;
+            let seat: usize = seat
+                .parse()
+                .map_err(|_| err(i, format!("invalid seat {seat:?}")))?;
+            if !in_summary {
+                let (name, stack) = rest
+                    .rsplit_once(" (")
+                    .ok_or_else(|| err(i, format!("invalid seat line {line:?}")))?;
+                let stack = stack.split_whitespace().next().and_then(parse_amount);
+                hand.players.push(Player {
+                    seat,
+                    name: name.to_string(),
+                    stack: stack.ok_or_else(|| err(i, format!("invalid stack in {line:?}")))?,
+                    hole_cards: None,
+                });
+            } else if let Some(player) = hand.players.iter_mut().find(|x| x.seat == seat) {
+                if let Some(cards) = rest
+                    .split_once(" showed ")
+                    .or_else(|| rest.split_once(" mucked "))
+                    .and_then(|(_, x)| parse_bracketed_cards(x))
+                {
+                    player.hole_cards = to_hole_cards(&cards);
+                }
+            }
+            continue;
+        }
+
+        if in_summary {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Dealt to ") {
+            if let Some((name, cards)) = rest.split_once(" [") {
+                let cards = parse_bracketed_cards(&format!("[{cards}"))
+                    .and_then(|x| to_hole_cards(&x))
+                    .ok_or_else(|| err(i, format!("invalid hole cards in {line:?}")))?;
+                hand.hero = Some(name.to_string());
+                if let Some(player) = hand.players.iter_mut().find(|x| x.name == name) {
+                    player.hole_cards = Some(cards);
+                }
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = rest
+                .split_once(") returned to ")
+                .ok_or_else(|| err(i, format!("invalid uncalled bet {line:?}")))?;
+            hand.actions.push(HandAction {
+                street,
+                player: name.to_string(),
+                kind: ActionKind::Uncalled(
+                    parse_amount(amount)
+                        .ok_or_else(|| err(i, format!("invalid amount {amount:?}")))?,
+                ),
+                all_in: false,
+            });
+            continue;
+        }
+
+        let Some(player) = hand
+            .players
+            .iter()
+            .map(|x| x.name.clone())
+            .filter(|name| line.starts_with(name.as_str()))
+            .max_by_key(|name| name.len())
+        else {
+            continue;
+        };
+        let rest = &line[player.len()..];
+        if let Some(rest) = rest.strip_prefix(" collected ") {
+            let amount = rest.split_whitespace().next().and_then(parse_amount);
+            hand.collected.push((
+                player,
+                amount.ok_or_else(|| err(i, format!("invalid amount in {line:?}")))?,
+            ));
+            continue;
+        }
+
+        let Some(rest) = rest.strip_prefix(": ") else {
+            continue;
+        };
+        if let Some(cards) = rest.strip_prefix("shows ") {
+            let cards = parse_bracketed_cards(cards).and_then(|x| to_hole_cards(&x));
+            if let Some(p) = hand.players.iter_mut().find(|x| x.name == player) {
+                p.hole_cards = cards.or(p.hole_cards);
+            }
+            continue;
+        }
+
+        let all_in = rest.ends_with("and is all-in");
+        let amount = |i: usize, s: &str| {
+            s.split_whitespace()
+                .next()
+                .and_then(parse_amount)
+                .ok_or_else(|| err(i, format!("invalid amount in {line:?}")))
+        };
+        let kind = if rest == "folds" {
+            ActionKind::Fold
+        } else if rest == "checks" {
+            ActionKind::Check
+        } else if let Some(x) = rest.strip_prefix("calls ") {
+            ActionKind::Call(amount(i, x)?)
+        } else if let Some(x) = rest.strip_prefix("bets ") {
+            ActionKind::Bet(amount(i, x)?)
+        } else if let Some(x) = rest.strip_prefix("raises ") {
+            let (_, to) = x
+                .split_once(" to ")
+                .ok_or_else(|| err(i, format!("invalid raise {line:?}")))?;
+            ActionKind::Raise(amount(i, to)?)
+        } else if let Some(x) = rest.strip_prefix("posts small blind ") {
+            ActionKind::PostSmallBlind(amount(i, x)?)
+        } else if let Some(x) = rest.strip_prefix("posts big blind ") {
+            ActionKind::PostBigBlind(amount(i, x)?)
+        } else if let Some(x) = rest.strip_prefix("posts the ante ") {
+            ActionKind::PostAnte(amount(i, x)?)
+        } else {
+            // chat, "mucks hand", "doesn't show hand", sitting out...
+            continue;
+        };
+        hand.actions.push(HandAction {
+            street,
+            player,
+            kind,
+            all_in,
+        });
+    }
+
+    Ok(hand)
+}
+
+/// `"... ($0.05/$0.10 USD) ..."` or `"... Level I (10/20) ..."`
+fn parse_stakes(header: &str) -> Option<(f64, f64)> {
+    header
+        .split('(')
+        .skip(1)
+        .filter_map(|x| x.split(')').next())
+        .find_map(|x| {
+            let (sb, bb) = x.split_once('/')?;
+            let bb = bb.split_whitespace().next()?;
+            Some((parse_amount(sb)?, parse_amount(bb)?))
+        })
+}
+
+fn parse_amount(s: &str) -> Option<f64> {
+    s.trim()
+        .trim_start_matches(['$', '€', '£', '¥'])
+        .replace(',', "")
+        .parse()
+        .ok()
+}
+
+/// Concatenates every `[..]` group: `"[Kc 7s 2c] [Kd]"` gives 4 cards
+fn parse_bracketed_cards(s: &str) -> Option<Vec<Card>> {
+    let mut cards = Vec::new();
+    for group in s.split('[').skip(1) {
+        let (group, _) = group.split_once(']')?;
+        for card in group.split_whitespace() {
+            cards.push(parse_card(card)?);
+        }
+    }
+    Some(cards)
+}
+
+fn to_hole_cards(cards: &[Card]) -> Option<[Card; 2]> {
+    match cards {
+        [a, b] => Some([*a, *b]),
+        _ => None,
+    }
+}
+
+fn board_len(street: Street) -> usize {
+    match street {
+        Street::Preflop => 0,
+        Street::Flop => 3,
+        Street::Turn => 4,
+        Street::River => 5,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StreetEquity {
+    pub street: Street,
+    pub pub_cards: Vec<Card>,
+    /// Players still in the hand when the street started, including the hero
+    pub n_players: usize,
+    /// `win + tie / 2` against random hands, from `Stage::win_rate_with_n_players`
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AllInEv {
+    pub street: Street,
+    /// Exact pot share against the hands shown down
+    pub equity: f64,
+    /// `equity * pot - invested`
+    pub ev: f64,
+    /// Actual result minus `ev`. Positive means we ran above expectation.
+    pub luck: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HandReplay {
+    pub id: String,
+    pub hero: String,
+    pub hole_cards: [Card; 2],
+    pub streets: Vec<StreetEquity>,
+    pub invested: f64,
+    pub collected: f64,
+    pub net: f64,
+    /// Only present when the money went in before the river and every hand was shown
+    pub all_in: Option<AllInEv>,
+}
+
+impl Display for HandReplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#{} {} {:?}", self.id, self.hero, self.hole_cards)?;
+        for street in self.streets.iter() {
+            writeln!(
+                f,
+                "  {:?} {:?} vs {}: {:.2}%",
+                street.street,
+                street.pub_cards,
+                street.n_players - 1,
+                street.equity * 100.
+            )?;
+        }
+        write!(f, "  net: {:.2}", self.net)?;
+        if let Some(all_in) = &self.all_in {
+            write!(
+                f,
+                ", all-in on {:?} with {:.2}%: ev {:.2}, luck {:.2}",
+                all_in.street,
+                all_in.equity * 100.,
+                all_in.ev,
+                all_in.luck
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+impl HandHistory {
+    /// The board as it was when `street` was dealt
+    pub fn board_at(&self, street: Street) -> &[Card] {
+        &self.board[..board_len(street).min(self.board.len())]
+    }
+
+    /// Total amount each player put into the pot, uncalled bets excluded
+    pub fn invested(&self) -> HashMap<&str, f64> {
+        let mut total: HashMap<&str, f64> = HashMap::new();
+        let mut street_total: HashMap<&str, f64> = HashMap::new();
+        let mut street = Street::Preflop;
+        for action in self.actions.iter() {
+            if action.street != street {
+                street = action.street;
+                street_total.clear();
+            }
+            let name = action.player.as_str();
+            let on_street = street_total.entry(name).or_insert(0.);
+            let added = match action.kind {
+                ActionKind::PostSmallBlind(x)
+                | ActionKind::PostBigBlind(x)
+                | ActionKind::Call(x)
+                | ActionKind::Bet(x) => x,
+                // antes are dead money and do not count towards the street bet
+                ActionKind::PostAnte(x) => {
+                    *total.entry(name).or_insert(0.) += x;
+                    continue;
+                }
+                ActionKind::Raise(to) => to - *on_street,
+                ActionKind::Uncalled(x) => -x,
+                ActionKind::Fold | ActionKind::Check => 0.,
+            };
+            *on_street += added;
+            *total.entry(name).or_insert(0.) += added;
+        }
+        total
+    }
+
+    /// Replays the hand from `hero`'s point of view. `None` if the hero's hole cards are unknown.
+    pub fn replay(&self, hero: &str) -> Option<HandReplay> {
+        let hole_cards = self.players.iter().find(|x| x.name == hero)?.hole_cards?;
+        let mut folded: Vec<&str> = Vec::new();
+        let mut streets = Vec::new();
+        let mut all_in_street = None;
+        for street in [Street::Preflop, Street::Flop, Street::Turn, Street::River] {
+            let pub_cards = self.board_at(street);
+            if pub_cards.len() < board_len(street) || folded.contains(&hero) {
+                break;
+            }
+            let n_players = self.players.len() - folded.len();
+            let odds = Stage::new(hole_cards, pub_cards).win_rate_with_n_players(n_players);
+            streets.push(StreetEquity {
+                street,
+                pub_cards: pub_cards.to_vec(),
+                n_players,
+                equity: odds.win + odds.tie / 2.,
+            });
+            for action in self.actions.iter().filter(|x| x.street == street) {
+                if action.kind == ActionKind::Fold {
+                    folded.push(&action.player);
+                }
+                if action.all_in {
+                    all_in_street = Some(street);
+                }
+            }
+        }
+
+        let invested = self.invested().get(hero).copied().unwrap_or(0.);
+        let collected: f64 = self
+            .collected
+            .iter()
+            .filter(|(name, _)| name == hero)
+            .map(|(_, x)| x)
+            .sum();
+        let net = collected - invested;
+        let all_in = all_in_street
+            .filter(|street| *street != Street::River && !folded.contains(&hero))
+            .and_then(|street| {
+                let mut hands = vec![hole_cards];
+                for player in self.players.iter() {
+                    if player.name != hero && !folded.contains(&player.name.as_str()) {
+                        hands.push(player.hole_cards?);
+                    }
+                }
+                if hands.len() < 2 {
+                    return None;
+                }
+                let equity = showdown_equity(&hands, self.board_at(street))[0];
+                let pot: f64 = self.collected.iter().map(|(_, x)| x).sum();
+                let ev = equity * pot - invested;
+                Some(AllInEv {
+                    street,
+                    equity,
+                    ev,
+                    luck: net - ev,
+                })
+            });
+
+        Some(HandReplay {
+            id: self.id.clone(),
+            hero: hero.to_string(),
+            hole_cards,
+            streets,
+            invested,
+            collected,
+            net,
+            all_in,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, ActionKind, Site};
+    use crate::sim::Street;
+
+    const POKERSTARS: &str = include_str!("../tests/fixtures/pokerstars.txt");
+    const GGPOKER: &str = include_str!("../tests/fixtures/ggpoker.txt");
+
+    #[test]
+    fn test_parse_pokerstars() {
+        let hands = parse(POKERSTARS).unwrap();
+        assert_eq!(hands.len(), 3);
+        let hand = &hands[0];
+        assert_eq!(hand.site, Site::PokerStars);
+        assert_eq!(hand.id, "243567890123");
+        assert_eq!((hand.small_blind, hand.big_blind), (0.05, 0.1));
+        assert_eq!(hand.button_seat, 1);
+        assert_eq!(hand.players.len(), 3);
+        assert_eq!(hand.players[2].name, "Nit Master");
+        assert_eq!(hand.players[2].stack, 9.15);
+        assert_eq!(hand.hero.as_deref(), Some("Hero"));
+        assert_eq!(hand.players[0].hole_cards, Some(["hA".into(), "dA".into()]));
+        assert_eq!(hand.players[1].hole_cards, Some(["sK".into(), "sQ".into()]));
+        assert_eq!(hand.board.len(), 5);
+        let raise = &hand.actions[8];
+        assert_eq!(raise.street, Street::Flop);
+        assert_eq!(raise.kind, ActionKind::Raise(9.7));
+        assert!(raise.all_in);
+        assert_eq!(hand.collected, vec![("fishy_joe".to_string(), 19.4)]);
+
+        let invested = hand.invested();
+        assert!((invested["Hero"] - 10.).abs() < 1e-9);
+        assert!((invested["Nit Master"] - 0.1).abs() < 1e-9);
+
+        // mucked cards from the summary
+        assert_eq!(
+            hands[1].players[1].hole_cards,
+            Some(["cA".into(), "dK".into()])
+        );
+        assert!((hands[2].invested()["Nit Master"] - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_ggpoker() {
+        let hands = parse(GGPOKER).unwrap();
+        assert_eq!(hands.len(), 1);
+        assert_eq!(hands[0].site, Site::GGPoker);
+        assert_eq!(hands[0].id, "RC1234567890");
+        assert_eq!(hands[0].hero.as_deref(), Some("Hero"));
+        assert_eq!(
+            hands[0].players[0].hole_cards,
+            Some(["d8".into(), "c8".into()])
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse(
+            "PokerStars Hand #1: Hold'em No Limit ($0.05/$0.10 USD)\nSeat x: Hero ($1 in chips)",
+        )
+        .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_replay() {
+        let hands = parse(POKERSTARS).unwrap();
+        let replay = hands[0].replay("Hero").unwrap();
+        assert_eq!(replay.streets.len(), 4);
+        assert_eq!(replay.streets[0].n_players, 3);
+        assert_eq!(replay.streets[1].n_players, 2);
+        assert!((replay.net + 10.).abs() < 1e-9);
+        let all_in = replay.all_in.unwrap();
+        assert_eq!(all_in.street, Street::Flop);
+        // aces against top pair: kings, queens or runner-runner spades save the underdog
+        assert!(all_in.equity > 0.75 && all_in.equity < 0.8);
+        assert!(all_in.ev > 0.);
+        assert!(all_in.luck < -10.);
+
+        // preflop all-in against a hand that was mucked
+        let replay = hands[1].replay("Hero").unwrap();
+        assert_eq!(replay.all_in.unwrap().street, Street::Preflop);
+        assert!((replay.net - 0.33).abs() < 1e-9);
+
+        let replay = hands[2].replay("Hero").unwrap();
+        assert!(replay.all_in.is_none());
+        assert!((replay.net + 0.05).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,39 @@
+//! Without the default `std` feature the crate is `no_std`: `texas` and `low` evaluate hands
+//! without allocating, and the `alloc` feature adds the odds and the other games.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// the tests use `vec!`, `format!` and `println!` in every build
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+pub mod board;
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "alloc")]
+pub mod chart;
+#[cfg(feature = "std")]
+pub mod history;
+pub mod low;
+#[cfg(feature = "alloc")]
+pub mod nuts;
+#[cfg(feature = "alloc")]
 pub mod odds;
+#[cfg(feature = "alloc")]
+pub mod omaha;
+#[cfg(feature = "alloc")]
+pub mod outs;
+#[cfg(feature = "alloc")]
+pub mod pineapple;
+#[cfg(feature = "std")]
+pub mod range;
+#[cfg(feature = "alloc")]
+pub mod scenario;
+#[cfg(feature = "std")]
 pub mod sim;
+#[cfg(feature = "alloc")]
+pub mod stud;
 pub mod texas;
@@ -0,0 +1,325 @@
+//! Low hand evaluation for lowball and split-pot games.
+use core::fmt::{Debug, Display};
+use heapless::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::texas::Hand;
+use crate::texas::{Card, CardNum};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LowRule {
+    /// A-5 (California) lowball: aces are low, straights and flushes don't count.
+    /// The best hand is 5-4-3-2-A. Used by Razz and the low half of hi-lo games.
+    AceToFive,
+    /// 2-7 (Kansas City) lowball: aces are high, straights and flushes count against you.
+    /// The best hand is 7-5-4-3-2 offsuit.
+    DeuceToSeven,
+}
+
+/// A five card low hand. Like `Hand`, a greater `LowHand` is the better hand.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LowHand {
+    rule: LowRule,
+    /// 0 = no pair, then pair, two pair... Straights and flushes are only ranked under 2-7.
+    category: u8,
+    /// Card values grouped by count then sorted from high to low
+    ranks: Vec<u8, 5>,
+}
+
+impl PartialOrd for LowHand {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LowHand {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        debug_assert_eq!(self.rule, other.rule);
+        // the lower the cards, the better the hand
+        (other.category, &other.ranks).cmp(&(self.category, &self.ranks))
+    }
+}
+
+impl Display for LowHand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, rank) in self.ranks.iter().enumerate() {
+            if i > 0 {
+                f.write_str("-")?;
+            }
+            match rank {
+                1 | 14 => f.write_str("A")?,
+                x => write!(f, "{}", CardNum::from(*x as usize))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LowHand {
+    pub fn rule(&self) -> LowRule {
+        self.rule
+    }
+
+    /// Whether the hand is an unpaired A-5 low of eight or lower, the qualifier of hi-lo games
+    pub fn is_eight_or_better(&self) -> bool {
+        self.rule == LowRule::AceToFive && self.category == 0 && self.ranks[0] <= 8
+    }
+}
+
+fn rank_value(num: CardNum, rule: LowRule) -> u8 {
+    match (num, rule) {
+        (CardNum::Ace, LowRule::AceToFive) => 1,
+        (CardNum::Ace, LowRule::DeuceToSeven) => 14,
+        (num, _) => usize::from(num) as u8,
+    }
+}
+
+/// Low value of exactly five cards
+pub fn calc_low(cards: &[Card], rule: LowRule) -> LowHand {
+    assert_eq!(cards.len(), 5);
+    let mut counts = [0u8; 15];
+    for card in cards {
+        counts[rank_value(card.num, rule) as usize] += 1;
+    }
+    let mut groups: Vec<(u8, u8), 5> = Vec::new();
+    for (rank, count) in counts.iter().enumerate().rev() {
+        if *count > 0 {
+            groups.push((*count, rank as u8)).unwrap();
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    let ranks: Vec<u8, 5> = groups
+        .iter()
+        .flat_map(|(count, rank)| core::iter::repeat(*rank).take(*count as usize))
+        .collect();
+    let pair_category = match (groups[0].0, groups.get(1).map(|x| x.0)) {
+        (4, _) => 5,
+        (3, Some(2)) => 4,
+        (3, _) => 3,
+        (2, Some(2)) => 2,
+        (2, _) => 1,
+        _ => 0,
+    };
+    let category = match rule {
+        LowRule::AceToFive => pair_category,
+        LowRule::DeuceToSeven => {
+            let flush = cards.iter().all(|x| x.suit == cards[0].suit);
+            let straight = groups.len() == 5 && ranks[0] - ranks[4] == 4;
+            // same order as `HandType`, with the royal flush folded into the straight flush
+            match (straight, flush, pair_category) {
+                (true, true, _) => 8,
+                (_, _, 5) => 7,
+                (_, _, 4) => 6,
+                (_, true, _) => 5,
+                (true, _, _) => 4,
+                (_, _, x) => x,
+            }
+        }
+    };
+    LowHand {
+        rule,
+        category,
+        ranks,
+    }
+}
+
+/// Best low made of any five of `cards`
+pub fn get_best_low(cards: &[Card], rule: LowRule) -> LowHand {
+    assert!(
+        cards.len() >= 5 && cards.len() <= 7,
+        "Invalid cards length {}",
+        cards.len()
+    );
+    let mut best: Option<LowHand> = None;
+    for mask in 0u8..1 << cards.len() {
+        if mask.count_ones() != 5 {
+            continue;
+        }
+        let five: Vec<Card, 5> = cards
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, card)| *card)
+            .collect();
+        let low = calc_low(&five, rule);
+        match &best {
+            Some(best) if low <= *best => {}
+            _ => best = Some(low),
+        }
+    }
+
+    best.unwrap()
+}
+
+/// Best eight-or-better low using exactly two `hole_cards` and three `pub_cards`, as in Omaha Hi-Lo
+pub fn get_best_omaha_low(hole_cards: &[Card], pub_cards: &[Card]) -> Option<LowHand> {
+    let mut best: Option<LowHand> = None;
+    for i in 0..hole_cards.len() {
+        for j in i + 1..hole_cards.len() {
+            for a in 0..pub_cards.len() {
+                for b in a + 1..pub_cards.len() {
+                    for c in b + 1..pub_cards.len() {
+                        let low = calc_low(
+                            &[
+                                hole_cards[i],
+                                hole_cards[j],
+                                pub_cards[a],
+                                pub_cards[b],
+                                pub_cards[c],
+                            ],
+                            LowRule::AceToFive,
+                        );
+                        if !low.is_eight_or_better() {
+                            continue;
+                        }
+                        match &best {
+                            Some(best) if low <= *best => {}
+                            _ => best = Some(low),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    best
+}
+
+/// Share of the pot won by each player in a hi-lo split pot.
+///
+/// The high half goes to the best `highs`, the low half to the best qualifying `lows`.
+/// Without any qualifying low the best high hand scoops. Ties split their half,
+/// so winning the high and tying the low with one player is worth 3/4 of the pot.
+#[cfg(feature = "alloc")]
+pub fn split_pot_shares(highs: &[Hand], lows: &[Option<LowHand>]) -> alloc::vec::Vec<f64> {
+    assert_eq!(highs.len(), lows.len());
+    let mut shares = alloc::vec![0.; highs.len()];
+    let best_low = lows.iter().flatten().max();
+    let high_pot = if best_low.is_some() { 0.5 } else { 1. };
+    award(&mut shares, highs.iter().map(Some), high_pot);
+    if best_low.is_some() {
+        award(&mut shares, lows.iter().map(|x| x.as_ref()), 0.5);
+    }
+    shares
+}
+
+#[cfg(feature = "alloc")]
+fn award<'a, T: Ord + 'a>(
+    shares: &mut [f64],
+    hands: impl Iterator<Item = Option<&'a T>> + Clone,
+    pot: f64,
+) {
+    let Some(best) = hands.clone().flatten().max() else {
+        return;
+    };
+    let n_winners = hands.clone().filter(|x| *x == Some(best)).count();
+    for (share, hand) in shares.iter_mut().zip(hands) {
+        if hand == Some(best) {
+            *share += pot / n_winners as f64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{string::ToString, vec::Vec};
+
+    use super::{calc_low, get_best_low, get_best_omaha_low, split_pot_shares, LowRule};
+    use crate::texas::{calc_hand, Card};
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|x| x.into()).collect()
+    }
+
+    #[test]
+    fn ace_to_five() {
+        let wheel = calc_low(&cards("1A 22 33 44 15"), LowRule::AceToFive);
+        let six_four = calc_low(&cards("16 24 33 42 1A"), LowRule::AceToFive);
+        let eight = calc_low(&cards("18 27 36 45 12"), LowRule::AceToFive);
+        let nine = calc_low(&cards("19 22 33 44 15"), LowRule::AceToFive);
+        let pair = calc_low(&cards("1A 2A 33 44 15"), LowRule::AceToFive);
+        assert!(wheel > six_four);
+        assert!(six_four > eight);
+        assert!(eight > nine);
+        assert!(nine > pair);
+        assert!(wheel.is_eight_or_better());
+        assert!(eight.is_eight_or_better());
+        assert!(!nine.is_eight_or_better());
+        assert!(!pair.is_eight_or_better());
+        assert_eq!(wheel.to_string(), "5-4-3-2-A");
+        // flushes don't count in A-5
+        assert_eq!(
+            calc_low(&cards("1A 12 13 14 15"), LowRule::AceToFive),
+            wheel
+        );
+    }
+
+    #[test]
+    fn deuce_to_seven() {
+        let number_one = calc_low(&cards("17 25 34 43 12"), LowRule::DeuceToSeven);
+        let eight = calc_low(&cards("18 25 34 43 12"), LowRule::DeuceToSeven);
+        let wheel = calc_low(&cards("1A 22 33 44 15"), LowRule::DeuceToSeven);
+        let straight = calc_low(&cards("16 25 34 43 12"), LowRule::DeuceToSeven);
+        let flush = calc_low(&cards("17 15 14 13 12"), LowRule::DeuceToSeven);
+        let pair = calc_low(&cards("17 25 34 42 12"), LowRule::DeuceToSeven);
+        assert!(number_one > eight);
+        // A-2-3-4-5 is ace high, not a straight
+        assert!(eight > wheel);
+        assert!(wheel > pair);
+        assert!(pair > straight);
+        assert!(straight > flush);
+        assert_eq!(number_one.to_string(), "7-5-4-3-2");
+    }
+
+    #[test]
+    fn best_low() {
+        let low = get_best_low(&cards("1K 2Q 1A 32 43 44 25"), LowRule::AceToFive);
+        assert_eq!(low.to_string(), "5-4-3-2-A");
+        let razz = get_best_low(&cards("1K 2K 3K 4Q 1Q 2J 1J"), LowRule::AceToFive);
+        assert_eq!(razz.to_string(), "Q-Q-J-J-K");
+    }
+
+    #[test]
+    fn omaha_low() {
+        // only one low card in hand: no low
+        let board = cards("12 23 37 4K 1Q");
+        assert!(get_best_omaha_low(&cards("1A 2K 3K 4Q"), &board).is_none());
+        let low = get_best_omaha_low(&cards("1A 24 3K 4Q"), &board).unwrap();
+        assert_eq!(low.to_string(), "7-4-3-2-A");
+        // counterfeited: the board pairs our low cards
+        let board = cards("1A 24 37 4K 1Q");
+        assert!(get_best_omaha_low(&cards("2A 44 3K 4Q"), &board).is_none());
+    }
+
+    #[test]
+    fn scoop_and_quarter() {
+        let board = cards("12 23 37 4K 1Q");
+        let high = |hole: &str| {
+            let mut all = cards(hole);
+            all.extend_from_slice(&board);
+            crate::odds::get_max_hand(&all)
+        };
+        let low = |s: &str| Some(calc_low(&cards(s), LowRule::AceToFive));
+
+        // no qualifying low: the high hand scoops
+        let shares = split_pot_shares(&[high("1K 2K"), high("1A 2A")], &[None, None]);
+        assert_eq!(shares, vec![1., 0.]);
+
+        // high for one, low for the other
+        let shares = split_pot_shares(
+            &[high("1K 2K"), high("1A 24")],
+            &[None, low("1A 24 12 23 37")],
+        );
+        assert_eq!(shares, vec![0.5, 0.5]);
+
+        // quartered: both have the same low, one also wins the high
+        let shares = split_pot_shares(
+            &[high("2K 3K"), high("1A 24"), high("4Q 3Q")],
+            &[low("1A 24 12 23 37"), low("2A 34 12 23 37"), None],
+        );
+        assert_eq!(shares, vec![0.75, 0.25, 0.]);
+
+        let five = calc_hand(&cards("1A 24 12 23 37"));
+        assert_eq!(split_pot_shares(&[five], &[None]), vec![1.]);
+    }
+}
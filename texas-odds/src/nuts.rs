@@ -0,0 +1,89 @@
+//! The nuts of a board: the best hands that two hole cards can make, and how many beat ours.
+use alloc::vec::Vec;
+
+use crate::{
+    odds::{get_all_holdings, get_best_hand},
+    texas::{Card, Hand},
+};
+
+/// Two hole cards and the hand they make with the board
+#[derive(Debug, PartialEq, Eq)]
+pub struct Holding {
+    pub cards: [Card; 2],
+    pub hand: Hand,
+}
+
+/// The `n` best holdings of the board, best first. Ties keep the order of the deck.
+pub fn top_hands(pub_cards: &[Card], n: usize) -> Vec<Holding> {
+    get_all_holdings(pub_cards, &[])
+        .into_iter()
+        .rev()
+        .take(n)
+        .map(|(hand, cards)| Holding { cards, hand })
+        .collect()
+}
+
+/// Every holding that makes the best possible hand on the board
+pub fn nuts(pub_cards: &[Card]) -> Vec<Holding> {
+    let mut holdings = get_all_holdings(pub_cards, &[]);
+    let best = holdings.len() - holdings.partition_point(|x| x.0 < holdings.last().unwrap().0);
+    holdings
+        .drain(holdings.len() - best..)
+        .rev()
+        .map(|(hand, cards)| Holding { cards, hand })
+        .collect()
+}
+
+/// Number of two card combinations that beat `hole_cards` on the board, our cards can't be held
+/// by anyone else
+pub fn combos_beating(hole_cards: [Card; 2], pub_cards: &[Card]) -> usize {
+    let mine: Vec<Card> = hole_cards.iter().chain(pub_cards).copied().collect();
+    let (hand, _) = get_best_hand(&mine);
+    let holdings = get_all_holdings(pub_cards, &hole_cards);
+    holdings.len() - holdings.partition_point(|x| x.0 <= hand)
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use super::{combos_beating, nuts, top_hands};
+    use crate::texas::{Card, HandType};
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(Card::from).collect()
+    }
+
+    #[test]
+    fn nut_flush() {
+        let board = cards("hK h7 h2 dQ s4");
+        let nuts = nuts(&board);
+        // the highest flush, every kicker counts
+        assert_eq!(nuts.len(), 1);
+        assert_eq!(nuts[0].hand.hand_type(), HandType::Flush);
+        assert!(nuts[0].cards.contains(&Card::from("hA")));
+        assert!(nuts[0].cards.contains(&Card::from("hQ")));
+
+        let top = top_hands(&board, 12);
+        assert_eq!(top.len(), 12);
+        assert!(top.windows(2).all(|x| x[0].hand >= x[1].hand));
+        assert_eq!(top[0].hand, nuts[0].hand);
+    }
+
+    #[test]
+    fn flop() {
+        // a set of kings is the best on a rainbow flop without straights
+        let board = cards("hK d7 c2");
+        let nuts = nuts(&board);
+        assert_eq!(nuts.len(), 3);
+        assert_eq!(nuts[0].hand.hand_type(), HandType::ThreeOfAKind);
+
+        assert_eq!(combos_beating(["sK".into(), "dK".into()], &board), 0);
+        // top pair top kicker is behind sets, two pairs and aces
+        // sets: 1 + 3 + 3, two pairs: K7 2*3 + K2 2*3 + 72 3*3, AA: 3
+        assert_eq!(
+            combos_beating(["sK".into(), "sA".into()], &board),
+            7 + 6 + 6 + 9 + 3
+        );
+    }
+}
@@ -1,21 +1,26 @@
-use heapless::Vec as HeaplessVec;
-use statistical::{mean, standard_deviation};
-use std::{
-    collections::BTreeMap,
+use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use core::{
     fmt::{Debug, Display},
-    iter,
+    sync::atomic::{AtomicBool, Ordering},
 };
+use heapless::Vec as HeaplessVec;
+use rand::Rng;
 
-use crate::texas::{calc_hand, iter_all_cards, Card, Hand, HandType};
+use crate::texas::{
+    calc_hand_with_variant, iter_all_cards_with_variant, Card, Hand, HandType, Variant,
+};
 
 #[derive(Debug)]
 pub struct Stage {
     pub_cards: HeaplessVec<Card, 5>,
     my_cards: [Card; 2],
+    variant: Variant,
+    /// Cards known to be out of play, e.g. a discarded hole card
+    dead_cards: Vec<Card>,
 }
 
 impl Display for Stage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "hole_cards: {:?}, community_cards: {:?}",
@@ -25,6 +30,7 @@ impl Display for Stage {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Odds {
     pub win: f64,
     pub tie: f64,
@@ -32,7 +38,7 @@ pub struct Odds {
 }
 
 impl Display for Odds {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(
             f,
             "win: {:.2}%, tie: {:.2}%",
@@ -44,6 +50,7 @@ impl Display for Odds {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WinRate {
     pub mean: f64,
     pub mean_tie_rate: f64,
@@ -58,6 +65,49 @@ pub struct WinRate {
     pub diff_rate: BTreeMap<HandType, f64>,
 }
 
+/// Where our hand ranks among every opponent holding on the current board
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandRanking {
+    /// Holdings we beat
+    pub beats: usize,
+    pub ties: usize,
+    /// Holdings that beat us, by hand type
+    pub beaten_by: BTreeMap<HandType, Vec<[Card; 2]>>,
+}
+
+impl HandRanking {
+    /// Every opponent holding
+    pub fn total(&self) -> usize {
+        self.beats + self.ties + self.loses()
+    }
+
+    pub fn loses(&self) -> usize {
+        self.beaten_by.values().map(Vec::len).sum()
+    }
+
+    /// Share of the holdings we beat, a tie counts as half
+    pub fn percentile(&self) -> f64 {
+        (self.beats as f64 + self.ties as f64 / 2.) / self.total() as f64
+    }
+}
+
+impl Display for HandRanking {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "beats {} of {} combos, ties {}",
+            self.beats,
+            self.total(),
+            self.ties
+        )?;
+        for (hand_type, holdings) in self.beaten_by.iter().rev() {
+            writeln!(f, "{:?}: {:?}", hand_type, holdings)?;
+        }
+        Ok(())
+    }
+}
+
 impl Stage {
     pub fn new(my_cards: [Card; 2], pub_cards: &[Card]) -> Self {
         let pub_cards = HeaplessVec::from_slice(pub_cards).unwrap();
@@ -69,7 +119,32 @@ impl Stage {
         Self {
             pub_cards,
             my_cards,
+            variant: Variant::Standard,
+            dead_cards: Vec::new(),
+        }
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        for card in self.my_cards.iter().chain(self.pub_cards.iter()) {
+            assert!(
+                variant.contains(card),
+                "{card} is not in the {variant:?} deck"
+            );
         }
+        self.variant = variant;
+        self
+    }
+
+    /// Excludes `dead_cards` from the runouts and the opponent hands
+    pub fn with_dead_cards(mut self, dead_cards: &[Card]) -> Self {
+        for card in dead_cards {
+            assert!(
+                !self.my_cards.contains(card) && !self.pub_cards.contains(card),
+                "{card} is already dealt"
+            );
+        }
+        self.dead_cards = dead_cards.to_vec();
+        self
     }
 
     pub fn win_rate(&self) -> WinRate {
@@ -81,21 +156,8 @@ impl Stage {
         //     println!("{}: {:?}", i, x);
         // });
         for hand in my_hands.iter() {
-            let without_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
-                std::cmp::Ordering::Equal => std::cmp::Ordering::Greater,
-                x => x,
-            }) {
-                Ok(i) => i,
-                Err(i) => i,
-            };
+            let (without_tie_rank, with_tie_rank) = rank_of(&all_hands, hand);
             let win_rate = without_tie_rank as f64 / all_hands.len() as f64;
-            let with_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
-                std::cmp::Ordering::Equal => std::cmp::Ordering::Less,
-                x => x,
-            }) {
-                Ok(i) => i,
-                Err(i) => i,
-            };
             let tie_rate = (with_tie_rank - without_tie_rank) as f64 / all_hands.len() as f64;
             win_rates.push(win_rate);
             tie_rates.push(tie_rate);
@@ -110,7 +172,7 @@ impl Stage {
             percentile25: win_rates[win_rates.len() / 4],
             percentile75: win_rates[win_rates.len() / 4 * 3],
             std: if win_rates.len() > 2 {
-                standard_deviation(&win_rates, None)
+                standard_deviation(&win_rates)
             } else {
                 0.
             },
@@ -135,56 +197,419 @@ impl Stage {
     fn enumerate_hands(&self) -> (Vec<Hand>, Vec<Hand>) {
         let (my_hands, all_hands) = if self.pub_cards.is_empty() {
             // FIXME: this is not accurate
-            let my_hands = fill_5_and_get_all_hands(&self.my_cards);
-            let all_hands = fill_5_and_get_all_hands(&[]);
+            let my_hands = fill_5_and_get_all_hands(&self.my_cards, &self.dead_cards, self.variant);
+            let all_hands = fill_5_and_get_all_hands(&[], &self.dead_cards, self.variant);
             (my_hands, all_hands)
         } else {
             let mut vec: HeaplessVec<Card, 7> = HeaplessVec::new();
             vec.extend_from_slice(&self.pub_cards).unwrap();
             vec.extend_from_slice(&self.my_cards).unwrap();
-            let my_hands = fill_7_and_get_all_hands(&vec);
-            let all_hands = fill_7_and_get_all_hands(&self.pub_cards);
+            let my_hands = fill_7_and_get_all_hands(&vec, &self.dead_cards, self.variant);
+            let all_hands =
+                fill_7_and_get_all_hands(&self.pub_cards, &self.dead_cards, self.variant);
             (my_hands, all_hands)
         };
         (my_hands, all_hands)
     }
 
+    /// `win_rate_with_n_players` that calls `progress` with the fraction done and the odds so far
+    /// after each of the `PROGRESS_STEPS` chunks, and stops once `cancel` is cancelled
+    pub fn win_rate_with_progress(
+        &self,
+        n: usize,
+        cancel: &CancelToken,
+        mut progress: impl FnMut(f64, &Odds),
+    ) -> Result<Odds, Cancelled> {
+        let mut job = OddsJob::new(self, n);
+        let chunk = job.chunk_size();
+        loop {
+            if cancel.is_cancelled() {
+                return Err(Cancelled);
+            }
+            let done = job.step(chunk);
+            let odds = job.odds();
+            progress(job.progress(), &odds);
+            if done {
+                return Ok(odds);
+            }
+        }
+    }
+
+    /// Exact rank of our hand among every opponent holding on the current flop, turn or river
+    pub fn hand_ranking(&self) -> HandRanking {
+        assert!(
+            !self.pub_cards.is_empty(),
+            "Invalid pub_cards length {}",
+            self.pub_cards.len()
+        );
+        assert_eq!(self.variant, Variant::Standard);
+        let mut cards: HeaplessVec<Card, 7> = HeaplessVec::new();
+        cards.extend_from_slice(&self.my_cards).unwrap();
+        cards.extend_from_slice(&self.pub_cards).unwrap();
+        let (hand, _) = get_best_hand(&cards);
+        let mut dead = self.dead_cards.clone();
+        dead.extend_from_slice(&self.my_cards);
+        let holdings = get_all_holdings(&self.pub_cards, &dead);
+
+        let without_tie_rank = holdings.partition_point(|x| x.0 < hand);
+        let with_tie_rank = holdings.partition_point(|x| x.0 <= hand);
+        let mut beaten_by: BTreeMap<HandType, Vec<[Card; 2]>> = BTreeMap::new();
+        for (other, cards) in holdings[with_tie_rank..].iter().rev() {
+            beaten_by.entry(other.hand_type()).or_default().push(*cards);
+        }
+        HandRanking {
+            beats: without_tie_rank,
+            ties: with_tie_rank - without_tie_rank,
+            beaten_by,
+        }
+    }
+
     pub fn win_rate_with_n_players(&self, n: usize) -> Odds {
         assert!(n >= 2, "n_players must be >= 2");
         let (my_hands, all_hands) = self.enumerate_hands();
-        let mut win_rates = Vec::with_capacity(my_hands.len());
-        let mut tie_rates = Vec::with_capacity(my_hands.len());
-        for hand in my_hands.iter() {
-            let without_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
-                std::cmp::Ordering::Equal => std::cmp::Ordering::Greater,
-                x => x,
-            }) {
-                Ok(i) => i,
-                Err(i) => i,
+        odds_with_n_players(&my_hands, &all_hands, n)
+    }
+}
+
+/// Runouts of the Monte Carlo odds of Omaha, Stud and hand ranges
+pub const DEFAULT_ITERATIONS: usize = 20_000;
+
+/// Moves a random draw of `n` cards to the front of `deck` and returns it, with a partial
+/// Fisher-Yates shuffle. A `rng` seeded the same way always draws the same cards.
+pub fn draw<'a>(deck: &'a mut [Card], n: usize, rng: &mut impl Rng) -> &'a [Card] {
+    assert!(
+        n <= deck.len(),
+        "Cannot draw {n} cards from {} cards",
+        deck.len()
+    );
+    for i in 0..n {
+        let j = rng.gen_range(i..deck.len());
+        deck.swap(i, j);
+    }
+    &deck[..n]
+}
+
+/// Number of times `Stage::win_rate_with_progress` reports its progress
+pub const PROGRESS_STEPS: usize = 32;
+
+/// Stops a running calculation, the clones share the same flag so it can be cancelled from
+/// another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("The calculation was cancelled")
+    }
+}
+
+impl core::error::Error for Cancelled {}
+
+/// `Stage::win_rate_with_n_players` a few opponent hands at a time.
+///
+/// The opponent hands are visited with a stride, so the hands done so far are spread over the
+/// whole deck and `odds` is already a good estimate before the job is done.
+pub struct OddsJob {
+    n_players: usize,
+    variant: Variant,
+    pub_cards: HeaplessVec<Card, 5>,
+    /// Cards left for the opponent hands
+    deck: Vec<Card>,
+    /// Number of cards drawn for each opponent hand
+    n_draw: usize,
+    my_hands: Vec<Hand>,
+    /// `rank_of` each of `my_hands` among the opponent hands counted so far
+    ranks: Vec<(usize, usize)>,
+    /// Opponent hands counted in `ranks`
+    counted: usize,
+    /// Evaluated but not counted yet
+    pending: Vec<Hand>,
+    /// Number of hands counted at once
+    chunk: usize,
+    total: usize,
+    done: usize,
+    /// The next combination to evaluate
+    index: usize,
+    offset: usize,
+}
+
+/// `BINOMIAL[n][k]` is `n` choose `k`
+const BINOMIAL: [[usize; 8]; 53] = {
+    let mut table = [[0; 8]; 53];
+    let mut n = 0;
+    while n < 53 {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k < 8 && k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+/// The `index`-th way to pick `n` cards of `deck`, in the order of `enumerate_n_cards`
+fn nth_combination(deck: &[Card], n: usize, mut index: usize, out: &mut HeaplessVec<Card, 7>) {
+    let mut i = 0;
+    for left in (1..=n).rev() {
+        loop {
+            let count = BINOMIAL[deck.len() - i - 1][left - 1];
+            if index < count {
+                break;
+            }
+            index -= count;
+            i += 1;
+        }
+        out.push(deck[i]).unwrap();
+        i += 1;
+    }
+}
+
+impl OddsJob {
+    pub fn new(stage: &Stage, n: usize) -> Self {
+        assert!(n >= 2, "n_players must be >= 2");
+        let (my_hands, n_draw) = if stage.pub_cards.is_empty() {
+            let my_hands =
+                fill_5_and_get_all_hands(&stage.my_cards, &stage.dead_cards, stage.variant);
+            (my_hands, 5)
+        } else {
+            let mut vec: HeaplessVec<Card, 7> = HeaplessVec::new();
+            vec.extend_from_slice(&stage.pub_cards).unwrap();
+            vec.extend_from_slice(&stage.my_cards).unwrap();
+            let my_hands = fill_7_and_get_all_hands(&vec, &stage.dead_cards, stage.variant);
+            (my_hands, 7 - stage.pub_cards.len())
+        };
+        let deck: Vec<Card> = iter_all_cards_with_variant(stage.variant)
+            .filter(|x| !stage.pub_cards.contains(x) && !stage.dead_cards.contains(x))
+            .collect();
+        let total = BINOMIAL[deck.len()][n_draw];
+        Self {
+            n_players: n,
+            variant: stage.variant,
+            pub_cards: stage.pub_cards.clone(),
+            deck,
+            n_draw,
+            ranks: vec![(0, 0); my_hands.len()],
+            my_hands,
+            counted: 0,
+            pending: Vec::new(),
+            chunk: (total / PROGRESS_STEPS).max(1),
+            total,
+            done: 0,
+            index: 0,
+            offset: 0,
+        }
+    }
+
+    /// Evaluates up to `max_hands` more opponent hands, returns whether the job is done
+    pub fn step(&mut self, max_hands: usize) -> bool {
+        let stride = PROGRESS_STEPS;
+        for _ in 0..max_hands {
+            if self.is_done() {
+                break;
+            }
+            let mut cards: HeaplessVec<Card, 7> = HeaplessVec::new();
+            nth_combination(&self.deck, self.n_draw, self.index, &mut cards);
+            let hand = if self.pub_cards.is_empty() {
+                calc_hand_with_variant(&cards, self.variant)
+            } else {
+                cards.extend_from_slice(&self.pub_cards).unwrap();
+                get_max_hand_with_variant(&cards, self.variant)
             };
-            let win_rate = without_tie_rank as f64 / all_hands.len() as f64;
-            let with_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
-                std::cmp::Ordering::Equal => std::cmp::Ordering::Less,
-                x => x,
-            }) {
-                Ok(i) => i,
-                Err(i) => i,
+            self.pending.push(hand);
+            self.done += 1;
+            self.index += stride;
+            if self.index >= self.total {
+                self.offset += 1;
+                self.index = self.offset;
+            }
+            if self.pending.len() >= self.chunk {
+                self.count_pending();
+            }
+        }
+        if self.is_done() {
+            self.count_pending();
+        }
+        self.is_done()
+    }
+
+    /// The ranks among all the hands are the sums of the ranks among each chunk
+    fn count_pending(&mut self) {
+        self.pending.sort_unstable();
+        for (hand, rank) in self.my_hands.iter().zip(self.ranks.iter_mut()) {
+            let (without_tie_rank, with_tie_rank) = rank_of(&self.pending, hand);
+            rank.0 += without_tie_rank;
+            rank.1 += with_tie_rank;
+        }
+        self.counted += self.pending.len();
+        self.pending.clear();
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done == self.total
+    }
+
+    /// Fraction of the opponent hands evaluated
+    pub fn progress(&self) -> f64 {
+        self.done as f64 / self.total as f64
+    }
+
+    /// Number of opponent hands in a chunk, `odds` only changes once a whole chunk is evaluated
+    pub fn chunk_size(&self) -> usize {
+        self.chunk
+    }
+
+    /// Odds against the opponent hands counted so far, exact once the job is done
+    pub fn odds(&self) -> Odds {
+        if self.counted == 0 {
+            return Odds {
+                win: 0.,
+                tie: 0.,
+                hand_rate: count_hand_type_freq(&self.my_hands),
             };
-            let tie_rate = (with_tie_rank - without_tie_rank) as f64 / all_hands.len() as f64;
-            let win_or_tie = win_rate + tie_rate;
+        }
+        odds_from_ranks(&self.my_hands, &self.ranks, self.counted, self.n_players)
+    }
+}
 
-            let lose_rate_with_n_players = 1. - win_or_tie.powi(n as i32);
-            let win_rate_with_n_players = win_rate.powi(n as i32);
-            let tie_rate_with_n_players = 1. - lose_rate_with_n_players - win_rate_with_n_players;
-            win_rates.push(win_rate_with_n_players);
-            tie_rates.push(tie_rate_with_n_players);
+/// Odds of `my_hands` against `n - 1` opponents, each holding a hand of the sorted `all_hands`
+pub(crate) fn odds_with_n_players(my_hands: &[Hand], all_hands: &[Hand], n: usize) -> Odds {
+    let ranks: Vec<(usize, usize)> = my_hands.iter().map(|x| rank_of(all_hands, x)).collect();
+    odds_from_ranks(my_hands, &ranks, all_hands.len(), n)
+}
+
+/// Number of the sorted `all_hands` below `hand`, and below or equal to it
+fn rank_of(all_hands: &[Hand], hand: &Hand) -> (usize, usize) {
+    let without_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
+        core::cmp::Ordering::Equal => core::cmp::Ordering::Greater,
+        x => x,
+    }) {
+        Ok(i) => i,
+        Err(i) => i,
+    };
+    let with_tie_rank = match all_hands.binary_search_by(|x| match x.cmp(hand) {
+        core::cmp::Ordering::Equal => core::cmp::Ordering::Less,
+        x => x,
+    }) {
+        Ok(i) => i,
+        Err(i) => i,
+    };
+    (without_tie_rank, with_tie_rank)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation
+fn standard_deviation(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    let variance =
+        values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (values.len() - 1) as f64;
+    sqrt(variance)
+}
+
+// `f64` has no `sqrt` and `powi` in `core`, these give the same results with and without `std`
+
+/// Square root by Newton's method, within an ulp of `f64::sqrt`. Zero, negative, infinite and
+/// NaN inputs are returned as they are.
+fn sqrt(x: f64) -> f64 {
+    if !x.is_finite() || x <= 0. {
+        return x;
+    }
+    // halving the exponent gives a guess within a few percent
+    let guess = f64::from_bits((x.to_bits() >> 1) + (0x3ff << 51));
+    // from the first step on the estimates decrease towards the root
+    let mut y = (guess + x / guess) / 2.;
+    loop {
+        let next = (y + x / y) / 2.;
+        if next >= y {
+            return y;
         }
-        Odds {
-            win: mean(&win_rates),
-            tie: mean(&tie_rates),
-            hand_rate: count_hand_type_freq(&my_hands),
+        y = next;
+    }
+}
+
+fn powi(x: f64, n: usize) -> f64 {
+    (0..n).fold(1., |acc, _| acc * x)
+}
+
+/// Odds of `my_hands` from their `rank_of` among `n_all` opponent hands
+fn odds_from_ranks(my_hands: &[Hand], ranks: &[(usize, usize)], n_all: usize, n: usize) -> Odds {
+    let mut win_rates = Vec::with_capacity(my_hands.len());
+    let mut tie_rates = Vec::with_capacity(my_hands.len());
+    for (without_tie_rank, with_tie_rank) in ranks.iter() {
+        let win_rate = *without_tie_rank as f64 / n_all as f64;
+        let tie_rate = (with_tie_rank - without_tie_rank) as f64 / n_all as f64;
+        let win_or_tie = win_rate + tie_rate;
+
+        let lose_rate_with_n_players = 1. - powi(win_or_tie, n);
+        let win_rate_with_n_players = powi(win_rate, n);
+        let tie_rate_with_n_players = 1. - lose_rate_with_n_players - win_rate_with_n_players;
+        win_rates.push(win_rate_with_n_players);
+        tie_rates.push(tie_rate_with_n_players);
+    }
+    Odds {
+        win: mean(&win_rates),
+        tie: mean(&tie_rates),
+        hand_rate: count_hand_type_freq(my_hands),
+    }
+}
+
+/// Exact share of the pot won by each of the known `hands` over all runouts of `pub_cards`
+///
+/// Ties split the pot, so the shares always add up to 1.
+pub fn showdown_equity(hands: &[[Card; 2]], pub_cards: &[Card]) -> Vec<f64> {
+    assert!(hands.len() >= 2, "at least two hands are needed");
+    assert!(
+        pub_cards.len() <= 5,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+    let dead: Vec<Card> = hands.iter().flatten().copied().collect();
+    let mut shares = vec![0.; hands.len()];
+    let mut n_runouts = 0;
+    for board in enumerate_n_cards(pub_cards, &dead, 5 - pub_cards.len(), Variant::Standard) {
+        let ranked: Vec<Hand> = hands
+            .iter()
+            .map(|hand| {
+                let mut cards = board.clone();
+                cards.extend_from_slice(hand).unwrap();
+                get_max_hand(&cards)
+            })
+            .collect();
+        let best = ranked.iter().max().unwrap();
+        let n_winners = ranked.iter().filter(|x| *x == best).count();
+        for (share, hand) in shares.iter_mut().zip(ranked.iter()) {
+            if hand == best {
+                *share += 1. / n_winners as f64;
+            }
         }
+        n_runouts += 1;
     }
+
+    for share in shares.iter_mut() {
+        *share /= n_runouts as f64;
+    }
+    shares
 }
 
 fn count_hand_type_freq(hands: &[Hand]) -> BTreeMap<HandType, f64> {
@@ -201,7 +626,39 @@ fn count_hand_type_freq(hands: &[Hand]) -> BTreeMap<HandType, f64> {
     map
 }
 
-fn get_max_hand(origin_cards: &[Card]) -> Hand {
+/// Best hand made of any five of 5 to 7 `cards`, and the five cards that make it
+pub fn get_best_hand(cards: &[Card]) -> (Hand, [Card; 5]) {
+    assert!(
+        cards.len() >= 5 && cards.len() <= 7,
+        "Invalid cards length {}",
+        cards.len()
+    );
+    let mut best: Option<(Hand, [Card; 5])> = None;
+    for mask in 0u8..1 << cards.len() {
+        if mask.count_ones() != 5 {
+            continue;
+        }
+        let five: HeaplessVec<Card, 5> = cards
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, card)| *card)
+            .collect();
+        let hand = calc_hand_with_variant(&five, Variant::Standard);
+        match &best {
+            Some((best, _)) if hand <= *best => {}
+            _ => best = Some((hand, five.into_array().unwrap())),
+        }
+    }
+
+    best.unwrap()
+}
+
+pub(crate) fn get_max_hand(origin_cards: &[Card]) -> Hand {
+    get_max_hand_with_variant(origin_cards, Variant::Standard)
+}
+
+pub(crate) fn get_max_hand_with_variant(origin_cards: &[Card], variant: Variant) -> Hand {
     assert_eq!(origin_cards.len(), 7);
     let mut max_hand = None;
     for i in 0..origin_cards.len() - 1 {
@@ -210,7 +667,7 @@ fn get_max_hand(origin_cards: &[Card]) -> Hand {
             cards.extend_from_slice(origin_cards).unwrap();
             cards.remove(j);
             cards.remove(i);
-            let hand = calc_hand(&cards);
+            let hand = calc_hand_with_variant(&cards, variant);
             if let Some(max_hand) = &mut max_hand {
                 if hand > *max_hand {
                     *max_hand = hand;
@@ -224,28 +681,64 @@ fn get_max_hand(origin_cards: &[Card]) -> Hand {
     max_hand.unwrap()
 }
 
-fn fill_5_and_get_all_hands(cards: &[Card]) -> Vec<Hand> {
+pub(crate) fn fill_5_and_get_all_hands(
+    cards: &[Card],
+    dead: &[Card],
+    variant: Variant,
+) -> Vec<Hand> {
     assert!(cards.len() <= 5);
-    let mut ans: Vec<Hand> = enumerate_n_cards(cards, 5 - cards.len())
-        .map(|x| calc_hand(&x))
+    let mut ans: Vec<Hand> = enumerate_n_cards(cards, dead, 5 - cards.len(), variant)
+        .map(|x| calc_hand_with_variant(&x, variant))
         .collect();
     ans.sort_unstable();
     ans
 }
 
-fn fill_7_and_get_all_hands(cards: &[Card]) -> Vec<Hand> {
+fn fill_7_and_get_all_hands(cards: &[Card], dead: &[Card], variant: Variant) -> Vec<Hand> {
     assert!(cards.len() >= 3);
-    let mut ans: Vec<Hand> = enumerate_n_cards(cards, 7 - cards.len())
-        .map(|x| get_max_hand(&x))
+    let mut ans: Vec<Hand> = enumerate_n_cards(cards, dead, 7 - cards.len(), variant)
+        .map(|x| get_max_hand_with_variant(&x, variant))
         .collect();
     ans.sort_unstable();
     ans
 }
 
-fn enumerate_n_cards(cards: &[Card], n: usize) -> impl Iterator<Item = HeaplessVec<Card, 7>> {
+/// Every two hole cards that can join the 3 to 5 `pub_cards`, with the hand they make,
+/// sorted like `fill_7_and_get_all_hands` but keeping the hole cards of each hand
+pub(crate) fn get_all_holdings(pub_cards: &[Card], dead: &[Card]) -> Vec<(Hand, [Card; 2])> {
+    assert!(
+        pub_cards.len() >= 3 && pub_cards.len() <= 5,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+    let mut ans: Vec<(Hand, [Card; 2])> = enumerate_n_cards(pub_cards, dead, 2, Variant::Standard)
+        .map(|x| {
+            // the new cards come first
+            let hole = [x[0], x[1]];
+            let hand = if x.len() == 7 {
+                get_max_hand(&x)
+            } else {
+                get_best_hand(&x).0
+            };
+            (hand, hole)
+        })
+        .collect();
+    ans.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    ans
+}
+
+/// Every way to add `n` cards to `cards`. The `dead` cards are never drawn.
+fn enumerate_n_cards(
+    cards: &[Card],
+    dead: &[Card],
+    n: usize,
+    variant: Variant,
+) -> impl Iterator<Item = HeaplessVec<Card, 7>> {
     let mut iter_stacks: HeaplessVec<usize, 7> = HeaplessVec::new();
     let mut all_cards: HeaplessVec<Card, 52> = HeaplessVec::new();
-    for card in iter_all_cards().filter(|x| !cards.contains(x)) {
+    for card in
+        iter_all_cards_with_variant(variant).filter(|x| !cards.contains(x) && !dead.contains(x))
+    {
         all_cards.push(card).unwrap();
     }
     for i in 0..n {
@@ -254,7 +747,7 @@ fn enumerate_n_cards(cards: &[Card], n: usize) -> impl Iterator<Item = HeaplessV
 
     let cards: Vec<_> = cards.to_vec();
     let mut empty_returned = false;
-    std::iter::from_fn(move || {
+    core::iter::from_fn(move || {
         if iter_stacks.is_empty() {
             if empty_returned {
                 return None;
@@ -308,8 +801,16 @@ mod wasm {}
 
 #[cfg(test)]
 mod test {
-    use super::{enumerate_n_cards, get_max_hand, Stage};
-    use crate::{odds::fill_7_and_get_all_hands, texas::HandType};
+    use std::{string::ToString, vec::Vec};
+
+    use super::{
+        draw, enumerate_n_cards, get_best_hand, get_max_hand, powi, showdown_equity, sqrt,
+        CancelToken, Cancelled, HeaplessVec, Stage, PROGRESS_STEPS,
+    };
+    use crate::{
+        odds::fill_7_and_get_all_hands,
+        texas::{iter_all_cards_with_variant, Card, HandType, Variant},
+    };
 
     #[test]
     fn test_max_hand() {
@@ -326,15 +827,35 @@ mod test {
         assert_eq!(hand.cmp_cards().to_vec(), vec!["1".into(), "8".into()]);
     }
 
+    #[test]
+    fn test_best_hand() {
+        let cards: Vec<Card> = ["1A", "1K", "3Q", "1J", "1T", "42", "1Q"]
+            .iter()
+            .map(|x| (*x).into())
+            .collect();
+        let (hand, five) = get_best_hand(&cards);
+        assert_eq!(hand.hand_type(), HandType::RoyalFlush);
+        assert!(!five.contains(&"3Q".into()));
+        assert_eq!(hand, get_max_hand(&cards));
+        let (hand, five) = get_best_hand(&cards[..5]);
+        assert_eq!(hand.hand_type(), HandType::Straight);
+        assert_eq!(five.as_slice(), &cards[..5]);
+        assert_eq!(get_best_hand(&cards[..6]).0.hand_type(), HandType::Straight);
+    }
+
     #[test]
     fn test_append_cards() {
-        dbg!(fill_7_and_get_all_hands(&[
-            "41".into(),
-            "31".into(),
-            "21".into(),
-            "11".into(),
-            "18".into(),
-        ],));
+        dbg!(fill_7_and_get_all_hands(
+            &[
+                "41".into(),
+                "31".into(),
+                "21".into(),
+                "11".into(),
+                "18".into(),
+            ],
+            &[],
+            Variant::Standard
+        ));
     }
 
     #[test]
@@ -368,14 +889,181 @@ mod test {
     }
 
     #[test]
-    fn should_i_wait_for_flush() {
-        let hands = fill_7_and_get_all_hands(&[
-            "41".into(),
-            "48".into(),
+    fn short_deck_win_rate() {
+        // the nut flush on a paired board only loses to full houses in hold'em,
+        // but a flush outranks them in short deck
+        let cards = ["1A".into(), "1K".into()];
+        let board = [
+            "16".into(),
+            "17".into(),
+            "1T".into(),
             "49".into(),
-            "44".into(),
-            "21".into(),
-        ]);
+            "39".into(),
+        ];
+        let standard = Stage::new(cards, &board).win_rate();
+        let short = Stage::new(cards, &board)
+            .with_variant(Variant::ShortDeck)
+            .win_rate();
+        assert_eq!(short.self_rate[&HandType::Flush], 1.);
+        assert!(short.mean > standard.mean);
+        assert!(short.mean > 0.99);
+    }
+
+    #[test]
+    fn sqrt_and_powi() {
+        for x in [1e-12, 0.0123, 0.25, 2., 3., 1e10] {
+            assert!((sqrt(x) - x.sqrt()).abs() <= f64::EPSILON * x.sqrt(), "{x}");
+        }
+        assert_eq!(sqrt(0.), 0.);
+        assert_eq!(sqrt(f64::INFINITY), f64::INFINITY);
+        assert!(sqrt(f64::NAN).is_nan());
+        assert_eq!(sqrt(4.), 2.);
+        assert_eq!(powi(0.5, 3), 0.125);
+        assert_eq!(powi(0.3, 0), 1.);
+    }
+
+    #[test]
+    fn seeded_draw() {
+        use rand::{rngs::SmallRng, SeedableRng};
+        let deck: Vec<Card> = crate::texas::iter_all_cards().collect();
+        let drawn = |seed| draw(&mut deck.clone(), 7, &mut SmallRng::seed_from_u64(seed)).to_vec();
+        assert_eq!(drawn(1), drawn(1));
+        assert_ne!(drawn(1), drawn(2));
+        let cards = drawn(1);
+        assert!(cards
+            .iter()
+            .enumerate()
+            .all(|(i, x)| !cards[..i].contains(x)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot draw 3 cards from 2 cards")]
+    fn draw_too_many() {
+        use rand::{rngs::SmallRng, SeedableRng};
+        draw(
+            &mut ["hA".into(), "dA".into()],
+            3,
+            &mut SmallRng::seed_from_u64(0),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn short_deck_rejects_small_cards() {
+        Stage::new(["12".into(), "1K".into()], &[]).with_variant(Variant::ShortDeck);
+    }
+
+    #[test]
+    fn dead_cards() {
+        let cards = ["1A".into(), "1K".into()];
+        let board = ["12".into(), "17".into(), "49".into()];
+        let live = Stage::new(cards, &board).win_rate();
+        assert!(live.self_rate[&HandType::Flush] > 0.);
+        // every other heart is dead
+        let dead = Stage::new(cards, &board)
+            .with_dead_cards(
+                &["13", "14", "15", "16", "18", "19", "1T", "1J", "1Q"].map(Card::from),
+            )
+            .win_rate();
+        assert!(!dead.self_rate.contains_key(&HandType::Flush));
+        assert!(dead.mean < live.mean);
+    }
+
+    #[test]
+    fn progress() {
+        let stage = Stage::new(
+            ["1A".into(), "2A".into()],
+            &["32".into(), "47".into(), "1Q".into()],
+        );
+        let exact = stage.win_rate_with_n_players(3);
+        let mut reports = Vec::new();
+        let odds = stage
+            .win_rate_with_progress(3, &CancelToken::new(), |done, odds| {
+                reports.push((done, odds.win))
+            })
+            .unwrap();
+        assert!((odds.win - exact.win).abs() < 1e-9);
+        assert!((odds.tie - exact.tie).abs() < 1e-9);
+        assert!(reports.len() >= PROGRESS_STEPS);
+        assert_eq!(reports.last().unwrap().0, 1.);
+        // the first chunk is already close
+        assert!((reports[0].1 - exact.win).abs() < 0.02);
+
+        let cancel = CancelToken::new();
+        let mut n_reports = 0;
+        let result = stage.win_rate_with_progress(3, &cancel, |_, _| {
+            n_reports += 1;
+            if n_reports == 2 {
+                cancel.cancel();
+            }
+        });
+        assert_eq!(result.unwrap_err(), Cancelled);
+        assert_eq!(n_reports, 2);
+    }
+
+    #[test]
+    fn nth_combination() {
+        let deck: Vec<Card> = iter_all_cards_with_variant(Variant::Standard).collect();
+        for (i, expected) in enumerate_n_cards(&[], &[], 3, Variant::Standard).enumerate() {
+            let mut cards = HeaplessVec::new();
+            super::nth_combination(&deck, 3, i, &mut cards);
+            assert_eq!(cards, expected);
+        }
+    }
+
+    #[test]
+    fn hand_ranking() {
+        // the second nut flush on the river
+        let ranking = Stage::new(
+            ["1K".into(), "13".into()],
+            &[
+                "1Q".into(),
+                "17".into(),
+                "12".into(),
+                "29".into(),
+                "44".into(),
+            ],
+        )
+        .hand_ranking();
+        assert_eq!(ranking.total(), 990);
+        assert_eq!(ranking.ties, 0);
+        // the ace of hearts with any of the 7 other hearts
+        assert_eq!(ranking.loses(), 7);
+        assert_eq!(ranking.beaten_by[&HandType::Flush].len(), 7);
+        assert!(ranking
+            .to_string()
+            .starts_with("beats 983 of 990 combos, ties 0"));
+
+        let ranking = Stage::new(
+            ["1K".into(), "13".into()],
+            &[
+                "1Q".into(),
+                "17".into(),
+                "12".into(),
+                "29".into(),
+                "44".into(),
+            ],
+        )
+        .with_dead_cards(&["1A".into()])
+        .hand_ranking();
+        assert_eq!(ranking.total(), 946);
+        assert_eq!(ranking.loses(), 0);
+        assert_eq!(ranking.percentile(), 1.);
+    }
+
+    #[test]
+    fn should_i_wait_for_flush() {
+        let hands = fill_7_and_get_all_hands(
+            &[
+                "41".into(),
+                "48".into(),
+                "49".into(),
+                "44".into(),
+                "21".into(),
+            ],
+            &[],
+            Variant::Standard,
+        );
         let flush_count = hands
             .iter()
             .filter(|x| x.hand_type() == HandType::Flush)
@@ -383,11 +1071,56 @@ mod test {
         println!("{}", flush_count as f64 / hands.len() as f64);
     }
 
+    #[test]
+    fn test_showdown_equity() {
+        // AA vs KK on a dry flop: KK needs one of the two kings without one of the two aces,
+        // or both kings
+        let shares = showdown_equity(
+            &[["1A".into(), "2A".into()], ["1K".into(), "2K".into()]],
+            &["32".into(), "47".into(), "3Q".into()],
+        );
+        let kk = (2. * 41. + 1.) / (45. * 44. / 2.);
+        assert!((shares[1] - kk).abs() < 1e-9);
+        assert!((shares[0] + shares[1] - 1.).abs() < 1e-9);
+
+        let shares = showdown_equity(
+            &[["1A".into(), "2K".into()], ["3A".into(), "4K".into()]],
+            &[
+                "32".into(),
+                "47".into(),
+                "39".into(),
+                "4J".into(),
+                "1Q".into(),
+            ],
+        );
+        assert_eq!(shares, vec![0.5, 0.5]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialize_odds() {
+        let odds = Stage::new(
+            ["1A".into(), "2A".into()],
+            &["3A".into(), "4A".into(), "1K".into()],
+        )
+        .win_rate_with_n_players(2);
+        let json = serde_json::to_value(&odds).unwrap();
+        assert_eq!(json["win"], odds.win);
+        assert_eq!(json["hand_rate"]["FourOfAKind"], 1.);
+        let back: super::Odds = serde_json::from_value(json).unwrap();
+        assert_eq!(back.hand_rate, odds.hand_rate);
+
+        let card = serde_json::to_string(&Card::from("1A")).unwrap();
+        assert_eq!(card, r#"{"suit":"Heart","num":"Ace"}"#);
+    }
+
     #[test]
     fn test_append() {
-        let cards = enumerate_n_cards(&[], 5);
+        let cards = enumerate_n_cards(&[], &[], 5, Variant::Standard);
         assert_eq!(cards.count(), 2598960);
-        let cards = enumerate_n_cards(&[], 4);
+        let cards = enumerate_n_cards(&[], &[], 4, Variant::Standard);
         assert_eq!(cards.count(), 52 * 51 * 50 * 49 / 4 / 3 / 2);
+        let cards = enumerate_n_cards(&[], &[], 5, Variant::ShortDeck);
+        assert_eq!(cards.count(), 36 * 35 * 34 * 33 * 32 / 5 / 4 / 3 / 2);
     }
 }
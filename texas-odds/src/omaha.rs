@@ -0,0 +1,348 @@
+//! Pot-Limit Omaha with 4 (PLO4) or 5 (PLO5) hole cards.
+//!
+//! The best hand must use exactly two hole cards and three community cards.
+use alloc::{vec, vec::Vec};
+use core::fmt::{Debug, Display};
+use heapless::Vec as HeaplessVec;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use crate::{
+    low::{get_best_omaha_low, split_pot_shares},
+    odds::{draw, Odds, DEFAULT_ITERATIONS},
+    texas::{calc_hand, iter_all_cards, Card, Hand, HandType},
+};
+
+/// Best hand made of exactly two `hole_cards` and three `pub_cards`
+pub fn get_max_omaha_hand(hole_cards: &[Card], pub_cards: &[Card]) -> Hand {
+    assert!(
+        hole_cards.len() >= 2,
+        "Invalid hole cards length {}",
+        hole_cards.len()
+    );
+    assert!(
+        pub_cards.len() >= 3 && pub_cards.len() <= 5,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+    let mut max_hand: Option<Hand> = None;
+    for i in 0..hole_cards.len() - 1 {
+        for j in i + 1..hole_cards.len() {
+            for a in 0..pub_cards.len() - 2 {
+                for b in a + 1..pub_cards.len() - 1 {
+                    for c in b + 1..pub_cards.len() {
+                        let hand = calc_hand(&[
+                            hole_cards[i],
+                            hole_cards[j],
+                            pub_cards[a],
+                            pub_cards[b],
+                            pub_cards[c],
+                        ]);
+                        match &max_hand {
+                            Some(max) if hand <= *max => {}
+                            _ => max_hand = Some(hand),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    max_hand.unwrap()
+}
+
+#[derive(Debug)]
+pub struct OmahaStage {
+    pub_cards: HeaplessVec<Card, 5>,
+    my_cards: HeaplessVec<Card, 5>,
+}
+
+impl Display for OmahaStage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "hole_cards: {:?}, community_cards: {:?}",
+            self.my_cards, self.pub_cards
+        )
+    }
+}
+
+impl OmahaStage {
+    pub fn new(my_cards: &[Card], pub_cards: &[Card]) -> Self {
+        assert!(
+            my_cards.len() == 4 || my_cards.len() == 5,
+            "Invalid hole cards length {}",
+            my_cards.len()
+        );
+        let pub_cards = HeaplessVec::from_slice(pub_cards).unwrap();
+        assert!(
+            (pub_cards.len() >= 3 && pub_cards.len() <= 5) || pub_cards.is_empty(),
+            "Invalid pub_cards length {}",
+            pub_cards.len()
+        );
+        let known: Vec<&Card> = my_cards.iter().chain(&pub_cards).collect();
+        for (i, card) in known.iter().enumerate() {
+            assert!(!known[..i].contains(card), "{card} is already dealt");
+        }
+        Self {
+            pub_cards,
+            my_cards: HeaplessVec::from_slice(my_cards).unwrap(),
+        }
+    }
+
+    /// Odds against `n - 1` random hands holding as many cards as ours,
+    /// estimated with `DEFAULT_ITERATIONS` simulated runouts
+    pub fn win_rate_with_n_players(&self, n: usize) -> Odds {
+        self.simulate(n, DEFAULT_ITERATIONS, 0)
+    }
+
+    /// Odds over `iterations` runouts and opponent hands drawn from a deck seeded with `seed`
+    pub fn simulate(&self, n: usize, iterations: usize, seed: u64) -> Odds {
+        let mut wins = 0;
+        let mut ties = 0;
+        let mut hand_count = [0usize; 10];
+        self.for_each_runout(n, iterations, seed, |board, others| {
+            let mine = get_max_omaha_hand(&self.my_cards, board);
+            hand_count[mine.hand_type() as usize] += 1;
+            let mut tie = false;
+            for hole in others {
+                let other = get_max_omaha_hand(hole, board);
+                match other.cmp(&mine) {
+                    core::cmp::Ordering::Greater => return,
+                    core::cmp::Ordering::Equal => tie = true,
+                    core::cmp::Ordering::Less => {}
+                }
+            }
+            if tie {
+                ties += 1;
+            } else {
+                wins += 1;
+            }
+        });
+
+        Odds {
+            win: wins as f64 / iterations as f64,
+            tie: ties as f64 / iterations as f64,
+            hand_rate: HandType::ALL
+                .iter()
+                .zip(hand_count.iter())
+                .filter(|(_, count)| **count > 0)
+                .map(|(hand, count)| (*hand, *count as f64 / iterations as f64))
+                .collect(),
+        }
+    }
+
+    /// Monte Carlo odds of Omaha Hi-Lo eight-or-better, where the pot is split between
+    /// the best high hand and the best qualifying low hand
+    pub fn simulate_hi_lo(&self, n: usize, iterations: usize, seed: u64) -> HiLoOdds {
+        let mut odds = HiLoOdds::default();
+        self.for_each_runout(n, iterations, seed, |board, others| {
+            let mut highs = vec![get_max_omaha_hand(&self.my_cards, board)];
+            let mut lows = vec![get_best_omaha_low(&self.my_cards, board)];
+            for hole in others {
+                highs.push(get_max_omaha_hand(hole, board));
+                lows.push(get_best_omaha_low(hole, board));
+            }
+            let shares = split_pot_shares(&highs, &lows);
+            let best_low = lows.iter().flatten().max();
+            odds.equity += shares[0];
+            if shares[0] == 1. {
+                odds.scoop += 1.;
+            }
+            if highs.iter().skip(1).all(|x| *x < highs[0]) {
+                odds.high += 1.;
+            }
+            if let (Some(mine), Some(best)) = (&lows[0], best_low) {
+                if mine == best {
+                    odds.low += 1.;
+                    if lows.iter().flatten().filter(|x| *x == best).count() > 1 {
+                        odds.quartered += 1.;
+                    }
+                }
+            }
+            if best_low.is_none() {
+                odds.no_low += 1.;
+            }
+        });
+
+        let n = iterations as f64;
+        odds.equity /= n;
+        odds.scoop /= n;
+        odds.high /= n;
+        odds.low /= n;
+        odds.quartered /= n;
+        odds.no_low /= n;
+        odds
+    }
+
+    /// Deals `iterations` random boards and opponent hands, calling `f(board, opponents)`
+    fn for_each_runout(
+        &self,
+        n: usize,
+        iterations: usize,
+        seed: u64,
+        mut f: impl FnMut(&[Card], core::slice::Chunks<Card>),
+    ) {
+        assert!(n >= 2, "n_players must be >= 2");
+        let n_hole = self.my_cards.len();
+        assert!(
+            n_hole * n + 5 <= 52,
+            "Not enough cards for {n} players with {n_hole} hole cards"
+        );
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut deck: Vec<Card> = iter_all_cards()
+            .filter(|x| !self.my_cards.contains(x) && !self.pub_cards.contains(x))
+            .collect();
+        let n_board = 5 - self.pub_cards.len();
+        let needed = n_board + n_hole * (n - 1);
+        for _ in 0..iterations {
+            let drawn = draw(&mut deck, needed, &mut rng);
+            let mut board: HeaplessVec<Card, 5> = self.pub_cards.clone();
+            board.extend_from_slice(&drawn[..n_board]).unwrap();
+            f(&board, drawn[n_board..].chunks(n_hole));
+        }
+    }
+}
+
+/// Omaha Hi-Lo results. Every field is a probability except `equity`, the expected share of the pot.
+#[derive(Debug, Clone, Default)]
+pub struct HiLoOdds {
+    pub equity: f64,
+    /// Winning the whole pot alone
+    pub scoop: f64,
+    /// Holding the best high hand alone
+    pub high: f64,
+    /// Holding the best qualifying low, alone or tied
+    pub low: f64,
+    /// Sharing the best low with another player
+    pub quartered: f64,
+    /// Nobody has a qualifying low and the high hand takes the whole pot
+    pub no_low: f64,
+}
+
+impl Display for HiLoOdds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "equity: {:.2}%, scoop: {:.2}%, high: {:.2}%, low: {:.2}%, quartered: {:.2}%, no low: {:.2}%",
+            self.equity * 100.,
+            self.scoop * 100.,
+            self.high * 100.,
+            self.low * 100.,
+            self.quartered * 100.,
+            self.no_low * 100.
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{get_max_omaha_hand, OmahaStage};
+    use crate::texas::HandType;
+
+    #[test]
+    #[should_panic(expected = "is already dealt")]
+    fn duplicate_cards() {
+        OmahaStage::new(
+            &["1A".into(), "2K".into(), "3K".into(), "47".into()],
+            &["1A".into(), "15".into(), "18".into()],
+        );
+    }
+
+    #[test]
+    fn must_use_two_hole_cards() {
+        // four hearts on board, one heart in hand: no flush in Omaha
+        let hand = get_max_omaha_hand(
+            &["1A".into(), "2K".into(), "3K".into(), "47".into()],
+            &[
+                "12".into(),
+                "15".into(),
+                "18".into(),
+                "19".into(),
+                "4Q".into(),
+            ],
+        );
+        assert_eq!(hand.hand_type(), HandType::Pair);
+
+        // quads on board only play as a full house at most with a pocket pair
+        let hand = get_max_omaha_hand(
+            &["1K".into(), "2K".into(), "33".into(), "44".into()],
+            &[
+                "19".into(),
+                "29".into(),
+                "39".into(),
+                "49".into(),
+                "1Q".into(),
+            ],
+        );
+        assert_eq!(hand.hand_type(), HandType::FullHouse);
+
+        // a single hole card can't complete the straight
+        let hand = get_max_omaha_hand(
+            &[
+                "1T".into(),
+                "22".into(),
+                "33".into(),
+                "44".into(),
+                "45".into(),
+            ],
+            &[
+                "1J".into(),
+                "2Q".into(),
+                "3K".into(),
+                "4A".into(),
+                "17".into(),
+            ],
+        );
+        assert_eq!(hand.hand_type(), HandType::HighCard);
+    }
+
+    #[test]
+    fn simulate() {
+        let stage = OmahaStage::new(&["1A".into(), "2A".into(), "1K".into(), "2K".into()], &[]);
+        let odds = stage.simulate(2, 5000, 1);
+        assert_eq!(odds.win, stage.simulate(2, 5000, 1).win);
+        // AAKK double suited is around a 2:1 favourite against a random hand
+        assert!(odds.win + odds.tie / 2. > 0.6 && odds.win + odds.tie / 2. < 0.75);
+        let total: f64 = odds.hand_rate.values().sum();
+        assert!((total - 1.).abs() < 1e-9);
+
+        let plo5 = OmahaStage::new(
+            &[
+                "1A".into(),
+                "2A".into(),
+                "1K".into(),
+                "2K".into(),
+                "3Q".into(),
+            ],
+            &["4A".into(), "3K".into(), "42".into()],
+        );
+        // top set is far ahead of its 1/6 share, but five PLO5 hands have plenty of draws
+        let odds = plo5.simulate(6, 2000, 1);
+        assert!(odds.win > 0.35 && odds.win < 0.55);
+    }
+
+    #[test]
+    fn simulate_hi_lo() {
+        // A-2 with a low board: the nut low is locked and the high is contested
+        let stage = OmahaStage::new(
+            &["1A".into(), "22".into(), "3K".into(), "4Q".into()],
+            &["13".into(), "24".into(), "37".into()],
+        );
+        let odds = stage.simulate_hi_lo(2, 5000, 1);
+        assert_eq!(odds.no_low, 0.);
+        assert!(odds.low > 0.9);
+        assert!(odds.equity > 0.5 && odds.equity < 1.);
+        assert!(odds.scoop <= odds.high);
+
+        // no low possible on a high board: the pot always goes to the high hand
+        let stage = OmahaStage::new(
+            &["1A".into(), "2A".into(), "3K".into(), "4Q".into()],
+            &["1K".into(), "2Q".into(), "3J".into()],
+        );
+        let odds = stage.simulate_hi_lo(2, 2000, 1);
+        assert_eq!(odds.no_low, 1.);
+        assert_eq!(odds.low, 0.);
+        let plain = stage.simulate(2, 2000, 1);
+        assert!((odds.equity - (plain.win + plain.tie / 2.)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,116 @@
+//! Outs: the cards of the next street that improve our hand.
+use alloc::vec::Vec;
+
+use crate::{
+    odds::get_best_hand,
+    texas::{iter_all_cards, Card, HandType},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Out {
+    pub card: Card,
+    /// Our hand type once the card is dealt
+    pub hand_type: HandType,
+}
+
+fn assert_flop_or_turn(pub_cards: &[Card]) {
+    assert!(
+        pub_cards.len() == 3 || pub_cards.len() == 4,
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+}
+
+/// Hand type made by the board alone, which has fewer than five cards on the turn
+fn board_hand_type(pub_cards: &[Card]) -> HandType {
+    if pub_cards.len() >= 5 {
+        return get_best_hand(pub_cards).0.hand_type();
+    }
+    let mut counts: Vec<usize> = pub_cards
+        .iter()
+        .map(|x| pub_cards.iter().filter(|y| y.num == x.num).count())
+        .collect();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    match counts.as_slice() {
+        [4, ..] => HandType::FourOfAKind,
+        [3, ..] => HandType::ThreeOfAKind,
+        [2, 2, 2, 2] => HandType::TwoPair,
+        [2, ..] => HandType::Pair,
+        _ => HandType::HighCard,
+    }
+}
+
+/// Cards that improve our hand type on the next street.
+///
+/// A card only counts when the improvement uses our hole cards,
+/// so pairing the board is not an out for a high card.
+pub fn outs(hole_cards: [Card; 2], pub_cards: &[Card]) -> Vec<Out> {
+    assert_flop_or_turn(pub_cards);
+    let known: Vec<Card> = hole_cards.iter().chain(pub_cards).copied().collect();
+    let current = get_best_hand(&known).0.hand_type();
+    let mut ans = Vec::new();
+    for card in iter_all_cards().filter(|x| !known.contains(x)) {
+        let mut cards = known.clone();
+        cards.push(card);
+        let hand_type = get_best_hand(&cards).0.hand_type();
+        if hand_type > current && hand_type > board_hand_type(&cards[2..]) {
+            ans.push(Out { card, hand_type });
+        }
+    }
+    ans
+}
+
+/// Cards of the next street that put us ahead of the known `other` hand
+pub fn outs_against(hole_cards: [Card; 2], other: [Card; 2], pub_cards: &[Card]) -> Vec<Card> {
+    assert_flop_or_turn(pub_cards);
+    let mine: Vec<Card> = hole_cards.iter().chain(pub_cards).copied().collect();
+    let theirs: Vec<Card> = other.iter().chain(pub_cards).copied().collect();
+    iter_all_cards()
+        .filter(|x| !mine.contains(x) && !other.contains(x))
+        .filter(|card| {
+            let mine: Vec<Card> = mine.iter().chain([card]).copied().collect();
+            let theirs: Vec<Card> = theirs.iter().chain([card]).copied().collect();
+            get_best_hand(&mine).0 > get_best_hand(&theirs).0
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{outs, outs_against};
+    use crate::texas::{Card, HandType};
+
+    #[test]
+    fn flush_draw() {
+        // the nine hearts left make the flush
+        let outs = outs(
+            ["1A".into(), "1K".into()],
+            &["12".into(), "17".into(), "49".into()],
+        );
+        let flushes = outs
+            .iter()
+            .filter(|x| x.hand_type == HandType::Flush)
+            .count();
+        assert_eq!(flushes, 9);
+        // and six cards pair one of our overcards
+        let pairs = outs
+            .iter()
+            .filter(|x| x.hand_type == HandType::Pair)
+            .count();
+        assert_eq!(pairs, 6);
+        // pairing the board is not an out
+        assert!(!outs.iter().any(|x| x.card == Card::from("22")));
+    }
+
+    #[test]
+    fn against() {
+        // AK against QQ on a low flop: three aces and three kings
+        let outs = outs_against(
+            ["1A".into(), "2K".into()],
+            ["1Q".into(), "2Q".into()],
+            &["32".into(), "47".into(), "39".into(), "44".into()],
+        );
+        assert_eq!(outs.len(), 6);
+        assert!(outs.contains(&Card::from("3A")));
+    }
+}
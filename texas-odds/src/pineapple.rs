@@ -0,0 +1,259 @@
+//! Pineapple and Crazy Pineapple, hold'em where every player is dealt three hole cards and discards one.
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+
+use heapless::Vec as HeaplessVec;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use crate::{
+    odds::{draw, get_max_hand, Odds, Stage, DEFAULT_ITERATIONS},
+    texas::{calc_hand, iter_all_cards, Card, HandType},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Discard {
+    /// 翻牌前弃牌
+    Pineapple,
+    /// 翻牌后弃牌
+    CrazyPineapple,
+}
+
+#[derive(Debug)]
+pub struct PineappleStage {
+    pub_cards: HeaplessVec<Card, 5>,
+    my_cards: [Card; 3],
+    discard: Discard,
+}
+
+/// The odds of the hand we keep after discarding `discard`
+#[derive(Debug, Clone)]
+pub struct DiscardOdds {
+    pub discard: Card,
+    pub kept: [Card; 2],
+    pub odds: Odds,
+}
+
+impl DiscardOdds {
+    /// Expected share of the pot, counting a tie as half a win
+    pub fn equity(&self) -> f64 {
+        self.odds.win + self.odds.tie / 2.
+    }
+}
+
+impl Display for PineappleStage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} hole_cards: {:?}, community_cards: {:?}",
+            self.discard, self.my_cards, self.pub_cards
+        )
+    }
+}
+
+impl Display for DiscardOdds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "discard {}, keep {} {}: win: {:.2}%, tie: {:.2}%",
+            self.discard,
+            self.kept[0],
+            self.kept[1],
+            self.odds.win * 100.,
+            self.odds.tie * 100.
+        )
+    }
+}
+
+impl PineappleStage {
+    /// `pub_cards` are the cards on board when we discard: none in Pineapple, the flop in Crazy Pineapple
+    pub fn new(discard: Discard, my_cards: [Card; 3], pub_cards: &[Card]) -> Self {
+        let pub_cards = HeaplessVec::from_slice(pub_cards).unwrap();
+        match discard {
+            Discard::Pineapple => assert!(
+                pub_cards.is_empty(),
+                "Invalid pub_cards length {}",
+                pub_cards.len()
+            ),
+            Discard::CrazyPineapple => assert!(
+                pub_cards.len() == 3,
+                "Invalid pub_cards length {}",
+                pub_cards.len()
+            ),
+        }
+        Self {
+            pub_cards,
+            my_cards,
+            discard,
+        }
+    }
+
+    /// Odds against `n - 1` players for each of the three discards, best first.
+    /// The discarded card is dead, it can't come on the board.
+    ///
+    /// In Pineapple the opponents are modeled as random two card hands, as if they had
+    /// discarded at random. In Crazy Pineapple they are dealt three cards and keep the two
+    /// that make their best hand on the flop, estimated with `DEFAULT_ITERATIONS` runouts.
+    pub fn discard_odds(&self, n: usize) -> Vec<DiscardOdds> {
+        let mut ans: Vec<DiscardOdds> = (0..3)
+            .map(|i| {
+                let discard = self.my_cards[i];
+                let kept = [self.my_cards[(i + 1) % 3], self.my_cards[(i + 2) % 3]];
+                let odds = match self.discard {
+                    Discard::Pineapple => Stage::new(kept, &self.pub_cards)
+                        .with_dead_cards(&[discard])
+                        .win_rate_with_n_players(n),
+                    Discard::CrazyPineapple => {
+                        self.simulate(kept, discard, n, DEFAULT_ITERATIONS, 0)
+                    }
+                };
+                DiscardOdds {
+                    discard,
+                    kept,
+                    odds,
+                }
+            })
+            .collect();
+        ans.sort_by(|a, b| b.equity().total_cmp(&a.equity()));
+        ans
+    }
+
+    /// Crazy Pineapple odds of `kept` over `iterations` runouts drawn from a deck seeded with
+    /// `seed`, against opponents that keep the best two of three cards on the flop
+    pub fn simulate(
+        &self,
+        kept: [Card; 2],
+        discard: Card,
+        n: usize,
+        iterations: usize,
+        seed: u64,
+    ) -> Odds {
+        assert_eq!(self.discard, Discard::CrazyPineapple);
+        assert!(n >= 2, "n_players must be >= 2");
+        assert!(
+            3 * n + 5 <= 52,
+            "Not enough cards for {n} players with 3 hole cards"
+        );
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut deck: Vec<Card> = iter_all_cards()
+            .filter(|x| !kept.contains(x) && *x != discard && !self.pub_cards.contains(x))
+            .collect();
+        let mut wins = 0;
+        let mut ties = 0;
+        let mut hand_count = [0usize; 10];
+        'runouts: for _ in 0..iterations {
+            let drawn = draw(&mut deck, 2 + 3 * (n - 1), &mut rng);
+            let mut board: HeaplessVec<Card, 7> = self.pub_cards.iter().copied().collect();
+            board.extend_from_slice(&drawn[..2]).unwrap();
+            board.extend_from_slice(&kept).unwrap();
+            let mine = get_max_hand(&board);
+            hand_count[mine.hand_type() as usize] += 1;
+            let mut tie = false;
+            for hole in drawn[2..].chunks(3) {
+                let hole = keep_best_two(hole, &self.pub_cards);
+                board.truncate(5);
+                board.extend_from_slice(&hole).unwrap();
+                match get_max_hand(&board).cmp(&mine) {
+                    core::cmp::Ordering::Greater => continue 'runouts,
+                    core::cmp::Ordering::Equal => tie = true,
+                    core::cmp::Ordering::Less => {}
+                }
+            }
+            if tie {
+                ties += 1;
+            } else {
+                wins += 1;
+            }
+        }
+
+        Odds {
+            win: wins as f64 / iterations as f64,
+            tie: ties as f64 / iterations as f64,
+            hand_rate: HandType::ALL
+                .iter()
+                .zip(hand_count.iter())
+                .filter(|(_, count)| **count > 0)
+                .map(|(hand, count)| (*hand, *count as f64 / iterations as f64))
+                .collect(),
+        }
+    }
+
+    /// The discard that keeps the most equity against `n - 1` players
+    pub fn best_discard(&self, n: usize) -> DiscardOdds {
+        self.discard_odds(n).remove(0)
+    }
+}
+
+/// The two of three `hole_cards` that make the best hand with the `flop`
+fn keep_best_two(hole_cards: &[Card], flop: &[Card]) -> [Card; 2] {
+    (0..3)
+        .map(|i| [hole_cards[(i + 1) % 3], hole_cards[(i + 2) % 3]])
+        .max_by_key(|kept| {
+            let mut cards: HeaplessVec<Card, 5> = HeaplessVec::from_slice(flop).unwrap();
+            cards.extend_from_slice(kept).unwrap();
+            calc_hand(&cards)
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Discard, PineappleStage};
+    use crate::texas::Card;
+
+    #[test]
+    fn pineapple() {
+        let stage = PineappleStage::new(
+            Discard::Pineapple,
+            ["1A".into(), "2A".into(), "37".into()],
+            &[],
+        );
+        let odds = stage.discard_odds(2);
+        assert_eq!(odds.len(), 3);
+        assert_eq!(odds[0].discard, Card::from("37"));
+        assert!(odds[0].equity() >= odds[1].equity());
+        assert!(odds[1].equity() >= odds[2].equity());
+    }
+
+    #[test]
+    fn crazy_pineapple() {
+        // a set of kings beats keeping the ace
+        let stage = PineappleStage::new(
+            Discard::CrazyPineapple,
+            ["1A".into(), "1K".into(), "2K".into()],
+            &["3K".into(), "47".into(), "32".into()],
+        );
+        let best = stage.best_discard(3);
+        assert_eq!(best.discard, Card::from("1A"));
+        assert!(best.odds.win > 0.8);
+    }
+
+    #[test]
+    fn crazy_pineapple_opponents_keep_two_of_three() {
+        // top pair with no kicker on A-7-3 wins about 82% of the time against a random two card
+        // hand, but only about 77% against the best two of three cards
+        let stage = PineappleStage::new(
+            Discard::CrazyPineapple,
+            ["1A".into(), "12".into(), "2K".into()],
+            &["2A".into(), "37".into(), "43".into()],
+        );
+        let kept = ["1A".into(), "12".into()];
+        let odds = stage.simulate(kept, "2K".into(), 2, 20_000, 0);
+        assert!((odds.win - 0.768).abs() < 0.02, "{}", odds.win);
+        assert_eq!(
+            odds.win,
+            stage.simulate(kept, "2K".into(), 2, 20_000, 0).win
+        );
+        // the king kicker is worth more than the deuce
+        assert_eq!(stage.best_discard(2).discard, Card::from("12"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn crazy_pineapple_discards_on_the_flop() {
+        PineappleStage::new(
+            Discard::CrazyPineapple,
+            ["1A".into(), "1K".into(), "2K".into()],
+            &[],
+        );
+    }
+}
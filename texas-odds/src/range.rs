@@ -0,0 +1,309 @@
+//! Hand ranges written in the usual rank first notation, e.g. `TT+, AKs, AQo+, KJs-K9s, AhKh`.
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::{
+    odds::{draw, get_max_hand, Odds},
+    texas::{iter_all_cards, parse_card, Card, CardNum, HandType, Suit},
+};
+
+const RANKS: [CardNum; 13] = [
+    CardNum::Two,
+    CardNum::Three,
+    CardNum::Four,
+    CardNum::Five,
+    CardNum::Six,
+    CardNum::Seven,
+    CardNum::Eight,
+    CardNum::Nine,
+    CardNum::Ten,
+    CardNum::Jack,
+    CardNum::Queen,
+    CardNum::King,
+    CardNum::Ace,
+];
+
+const SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Club, Suit::Spade];
+
+/// Every two card combination of a range, without duplicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range {
+    combos: Vec<[Card; 2]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRangeError(pub String);
+
+impl Display for ParseRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid range {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRangeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Suitedness {
+    Pair,
+    Suited,
+    Offsuit,
+    Any,
+}
+
+/// `AKs`, `QQ` or `T9` as rank indexes into `RANKS`, the high card first
+fn parse_class(s: &str) -> Option<(usize, usize, Suitedness)> {
+    let rank = |c: char| "23456789TJQKA".find(c.to_ascii_uppercase());
+    let mut chars = s.chars();
+    let a = rank(chars.next()?)?;
+    let b = rank(chars.next()?)?;
+    let suitedness = match (chars.next(), a == b) {
+        (None, true) => Suitedness::Pair,
+        (None, false) => Suitedness::Any,
+        (Some('s' | 'S'), false) => Suitedness::Suited,
+        (Some('o' | 'O'), false) => Suitedness::Offsuit,
+        _ => return None,
+    };
+    if chars.next().is_some() {
+        return None;
+    }
+    Some((a.max(b), a.min(b), suitedness))
+}
+
+fn push_class(combos: &mut Vec<[Card; 2]>, high: usize, low: usize, suitedness: Suitedness) {
+    for (i, a) in SUITS.iter().enumerate() {
+        for (j, b) in SUITS.iter().enumerate() {
+            let keep = match suitedness {
+                Suitedness::Pair => i < j,
+                Suitedness::Suited => i == j,
+                Suitedness::Offsuit => i != j,
+                Suitedness::Any => true,
+            };
+            if keep {
+                combos.push([
+                    Card {
+                        suit: *a,
+                        num: RANKS[high],
+                    },
+                    Card {
+                        suit: *b,
+                        num: RANKS[low],
+                    },
+                ]);
+            }
+        }
+    }
+}
+
+fn parse_token(token: &str, combos: &mut Vec<[Card; 2]>) -> Option<()> {
+    if let (Some(a), Some(b)) = (
+        token.get(..2).and_then(parse_card),
+        token.get(2..).and_then(parse_card),
+    ) {
+        if a != b {
+            combos.push([a, b]);
+            return Some(());
+        }
+        return None;
+    }
+
+    if let Some((from, to)) = token.split_once('-') {
+        // `22-55` or `K9s-KJs`, in either order
+        let (high_a, low_a, suited_a) = parse_class(from)?;
+        let (high_b, low_b, suited_b) = parse_class(to)?;
+        if suited_a != suited_b {
+            return None;
+        }
+        if suited_a == Suitedness::Pair {
+            for rank in low_a.min(low_b)..=low_a.max(low_b) {
+                push_class(combos, rank, rank, suited_a);
+            }
+        } else {
+            if high_a != high_b {
+                return None;
+            }
+            for low in low_a.min(low_b)..=low_a.max(low_b) {
+                push_class(combos, high_a, low, suited_a);
+            }
+        }
+        return Some(());
+    }
+
+    let (class, plus) = match token.strip_suffix('+') {
+        Some(class) => (class, true),
+        None => (token, false),
+    };
+    let (high, low, suitedness) = parse_class(class)?;
+    match (plus, suitedness) {
+        (false, _) => push_class(combos, high, low, suitedness),
+        // `TT+` is every pair from tens up
+        (true, Suitedness::Pair) => {
+            for rank in low..RANKS.len() {
+                push_class(combos, rank, rank, suitedness);
+            }
+        }
+        // `ATs+` raises the kicker up to `AKs`
+        (true, _) => {
+            for low in low..high {
+                push_class(combos, high, low, suitedness);
+            }
+        }
+    }
+    Some(())
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut combos = Vec::new();
+        for token in s.split([',', ' ']).map(str::trim).filter(|x| !x.is_empty()) {
+            parse_token(token, &mut combos).ok_or_else(|| ParseRangeError(token.to_string()))?;
+        }
+        let mut range = Range { combos: Vec::new() };
+        for combo in combos {
+            if !range.contains(&combo) {
+                range.combos.push(combo);
+            }
+        }
+        Ok(range)
+    }
+}
+
+impl Range {
+    pub fn combos(&self) -> &[[Card; 2]] {
+        &self.combos
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    pub fn contains(&self, hand: &[Card; 2]) -> bool {
+        self.combos
+            .iter()
+            .any(|[a, b]| (*a == hand[0] && *b == hand[1]) || (*a == hand[1] && *b == hand[0]))
+    }
+}
+
+/// Monte Carlo odds of `hole_cards` against one opponent holding a random hand of `range`.
+/// Hands of the range that share a card with ours or the board are skipped.
+pub fn equity_vs_range(
+    hole_cards: [Card; 2],
+    range: &Range,
+    pub_cards: &[Card],
+    iterations: usize,
+    seed: u64,
+) -> Odds {
+    assert!(
+        (pub_cards.len() >= 3 && pub_cards.len() <= 5) || pub_cards.is_empty(),
+        "Invalid pub_cards length {}",
+        pub_cards.len()
+    );
+    let known: Vec<Card> = hole_cards.iter().chain(pub_cards).copied().collect();
+    let combos: Vec<&[Card; 2]> = range
+        .combos
+        .iter()
+        .filter(|combo| !combo.iter().any(|x| known.contains(x)))
+        .collect();
+    assert!(!combos.is_empty(), "No hand of the range is possible");
+
+    let mut deck: Vec<Card> = iter_all_cards().filter(|x| !known.contains(x)).collect();
+    let n_board = 5 - pub_cards.len();
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut wins = 0;
+    let mut ties = 0;
+    let mut hand_count: BTreeMap<HandType, usize> = BTreeMap::new();
+    for _ in 0..iterations {
+        let other = combos[rng.gen_range(0..combos.len())];
+        // two extra cards in case the opponent holds some of them
+        let drawn = draw(&mut deck, n_board + 2, &mut rng);
+        let mut mine: heapless::Vec<Card, 7> = known.iter().copied().collect();
+        mine.extend(
+            drawn
+                .iter()
+                .filter(|x| !other.contains(x))
+                .take(n_board)
+                .copied(),
+        );
+        let mut theirs = mine.clone();
+        theirs[0] = other[0];
+        theirs[1] = other[1];
+
+        let mine = get_max_hand(&mine);
+        *hand_count.entry(mine.hand_type()).or_insert(0) += 1;
+        match mine.cmp(&get_max_hand(&theirs)) {
+            std::cmp::Ordering::Greater => wins += 1,
+            std::cmp::Ordering::Equal => ties += 1,
+            std::cmp::Ordering::Less => {}
+        }
+    }
+
+    Odds {
+        win: wins as f64 / iterations as f64,
+        tie: ties as f64 / iterations as f64,
+        hand_rate: hand_count
+            .into_iter()
+            .map(|(hand, count)| (hand, count as f64 / iterations as f64))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{equity_vs_range, Range};
+    use crate::{odds::showdown_equity, texas::Card};
+
+    fn combos(s: &str) -> usize {
+        s.parse::<Range>().unwrap().len()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(combos("AA"), 6);
+        assert_eq!(combos("AKs"), 4);
+        assert_eq!(combos("AKo"), 12);
+        assert_eq!(combos("AK"), 16);
+        assert_eq!(combos("TT+"), 5 * 6);
+        assert_eq!(combos("22+"), 13 * 6);
+        assert_eq!(combos("ATs+"), 4 * 4);
+        assert_eq!(combos("KJo-K9o"), 3 * 12);
+        assert_eq!(combos("55-33"), 3 * 6);
+        assert_eq!(combos("AhKh"), 1);
+        // duplicates are only counted once
+        assert_eq!(combos("AKs, AhKh, AK"), 16);
+        assert_eq!(combos(""), 0);
+
+        let range: Range = "QQ+,AKs".parse().unwrap();
+        assert!(range.contains(&[Card::from("1K"), Card::from("1A")]));
+        assert!(!range.contains(&[Card::from("1K"), Card::from("2A")]));
+
+        for invalid in ["AKx", "AAs", "A", "AKs-QJs", "22-AKs", "AhAh", "X2", "AhK♥"] {
+            assert_eq!(
+                invalid.parse::<Range>().unwrap_err().to_string(),
+                format!("Invalid range {invalid}")
+            );
+        }
+    }
+
+    #[test]
+    fn equity() {
+        let board = ["32".into(), "47".into(), "3Q".into()];
+        let aa = ["1A".into(), "2A".into()];
+        // a range of one hand matches the exact showdown equity
+        let odds = equity_vs_range(aa, &"KhKd".parse().unwrap(), &board, 20_000, 1);
+        let exact = showdown_equity(&[aa, ["1K".into(), "2K".into()]], &board)[0];
+        assert!((odds.win + odds.tie / 2. - exact).abs() < 0.01);
+
+        // hands that conflict with ours are skipped
+        let odds = equity_vs_range(aa, &"AA".parse().unwrap(), &board, 2000, 1);
+        assert!(odds.tie > 0.9);
+        assert_eq!(
+            odds.win,
+            equity_vs_range(aa, &"AA".parse().unwrap(), &board, 2000, 1).win
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! A single spot to evaluate, parsed from one line of text or JSON.
+//!
+//! Text lines look like the CLI arguments, with optional `n=` and `dead=` fields:
+//!
+//! > h2 d3 s4 c5 d13 n=3 dead=hA,sK
+//!
+//! JSON lines use the same names as the JSON output:
+//!
+//! > {"hole_cards": ["h2", "d3"], "community_cards": ["s4", "c5", "d13"], "n_players": 3, "dead_cards": ["hA"]}
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Display, str::FromStr};
+
+use crate::{
+    odds::{Odds, Stage},
+    texas::{Card, ParseCardError},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    pub hole_cards: [Card; 2],
+    pub community_cards: Vec<Card>,
+    pub n_players: usize,
+    pub dead_cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioError {
+    pub message: String,
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for ScenarioError {}
+
+impl From<ParseCardError> for ScenarioError {
+    fn from(value: ParseCardError) -> Self {
+        ScenarioError {
+            message: value.to_string(),
+        }
+    }
+}
+
+fn err<T>(message: impl Into<String>) -> Result<T, ScenarioError> {
+    Err(ScenarioError {
+        message: message.into(),
+    })
+}
+
+/// The checks of `Scenario::new` but the number of hole cards, for a spot whose hole cards may
+/// not be known yet: the community cards length, the number of players and that no card is used
+/// twice
+pub fn check_spot(
+    hole_cards: &[Card],
+    community_cards: &[Card],
+    n_players: usize,
+    dead_cards: &[Card],
+) -> Result<(), ScenarioError> {
+    if !(community_cards.is_empty() || (3..=5).contains(&community_cards.len())) {
+        return err(format!(
+            "Invalid community cards length {}",
+            community_cards.len()
+        ));
+    }
+    if n_players < 2 {
+        return err("n_players must be >= 2");
+    }
+    let all: Vec<&Card> = hole_cards
+        .iter()
+        .chain(community_cards)
+        .chain(dead_cards)
+        .collect();
+    for (i, card) in all.iter().enumerate() {
+        if all[..i].contains(card) {
+            return err(format!("{card} is used twice"));
+        }
+    }
+    Ok(())
+}
+
+impl Scenario {
+    /// Checks the card counts, the number of players and that no card is used twice
+    pub fn new(
+        hole_cards: &[Card],
+        community_cards: &[Card],
+        n_players: usize,
+        dead_cards: &[Card],
+    ) -> Result<Self, ScenarioError> {
+        let Ok(hole_cards) = <[Card; 2]>::try_from(hole_cards) else {
+            return err(format!("Expected 2 hole cards, got {}", hole_cards.len()));
+        };
+        check_spot(&hole_cards, community_cards, n_players, dead_cards)?;
+        Ok(Scenario {
+            hole_cards,
+            community_cards: community_cards.to_vec(),
+            n_players,
+            dead_cards: dead_cards.to_vec(),
+        })
+    }
+
+    pub fn stage(&self) -> Stage {
+        Stage::new(self.hole_cards, &self.community_cards).with_dead_cards(&self.dead_cards)
+    }
+
+    pub fn odds(&self) -> Odds {
+        self.stage().win_rate_with_n_players(self.n_players)
+    }
+
+    fn parse_text(line: &str) -> Result<Self, ScenarioError> {
+        let mut cards = Vec::new();
+        let mut n_players = 2;
+        let mut dead_cards = Vec::new();
+        for token in line.split_whitespace() {
+            if let Some(n) = token.strip_prefix("n=") {
+                let Ok(n) = n.parse() else {
+                    return err(format!("Invalid number of players {n}"));
+                };
+                n_players = n;
+            } else if let Some(dead) = token.strip_prefix("dead=") {
+                for card in dead.split(',').filter(|x| !x.is_empty()) {
+                    dead_cards.push(card.parse()?);
+                }
+            } else {
+                cards.push(token.parse()?);
+            }
+        }
+        if cards.len() < 2 {
+            return err(format!("Expected 2 hole cards, got {}", cards.len()));
+        }
+        Scenario::new(&cards[..2], &cards[2..], n_players, &dead_cards)
+    }
+
+    #[cfg(feature = "json")]
+    fn parse_json(line: &str) -> Result<Self, ScenarioError> {
+        #[derive(serde::Deserialize)]
+        struct Line {
+            hole_cards: Vec<String>,
+            #[serde(default)]
+            community_cards: Vec<String>,
+            n_players: Option<usize>,
+            #[serde(default)]
+            dead_cards: Vec<String>,
+        }
+
+        let parse = |cards: &[String]| -> Result<Vec<Card>, ParseCardError> {
+            cards.iter().map(|x| x.parse()).collect()
+        };
+        let line: Line = match serde_json::from_str(line) {
+            Ok(line) => line,
+            Err(e) => return err(e.to_string()),
+        };
+        Scenario::new(
+            &parse(&line.hole_cards)?,
+            &parse(&line.community_cards)?,
+            line.n_players.unwrap_or(2),
+            &parse(&line.dead_cards)?,
+        )
+    }
+
+    #[cfg(not(feature = "json"))]
+    fn parse_json(_: &str) -> Result<Self, ScenarioError> {
+        err("JSON input needs the json feature")
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            Scenario::parse_json(line)
+        } else {
+            Scenario::parse_text(line)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::string::ToString;
+
+    use super::Scenario;
+    use crate::texas::Card;
+
+    #[test]
+    fn parse_text() {
+        let scenario: Scenario = "h2 d3 s4 c5 d13 n=3 dead=hA,sK".parse().unwrap();
+        assert_eq!(scenario.hole_cards, [Card::from("h2"), Card::from("d3")]);
+        assert_eq!(scenario.community_cards.len(), 3);
+        assert_eq!(scenario.n_players, 3);
+        assert_eq!(
+            scenario.dead_cards,
+            vec![Card::from("hA"), Card::from("sK")]
+        );
+
+        let scenario: Scenario = "  1A 2A ".parse().unwrap();
+        assert!(scenario.community_cards.is_empty());
+        assert_eq!(scenario.n_players, 2);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn parse_json() {
+        let scenario: Scenario = r#"{"hole_cards": ["h2", "d3"], "community_cards": ["s4", "c5", "d13"], "n_players": 3, "dead_cards": ["hA"]}"#
+            .parse()
+            .unwrap();
+        assert_eq!(scenario, "h2 d3 s4 c5 d13 n=3 dead=hA".parse().unwrap());
+        let scenario: Scenario = r#"{"hole_cards": ["h2", "d3"]}"#.parse().unwrap();
+        assert_eq!(scenario, "h2 d3".parse().unwrap());
+        assert!(r#"{"hole_cards": "#.parse::<Scenario>().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        let message = |line: &str| line.parse::<Scenario>().unwrap_err().to_string();
+        assert_eq!(message("h2"), "Expected 2 hole cards, got 1");
+        assert_eq!(message("h2 d3 s4"), "Invalid community cards length 1");
+        assert_eq!(message("h2 x3"), "Invalid card x3");
+        assert_eq!(message("h2 d3 n=1"), "n_players must be >= 2");
+        assert_eq!(message("h2 d3 n=many"), "Invalid number of players many");
+        assert_eq!(message("h2 d3 s4 c5 h2"), "♥️2 is used twice");
+        assert_eq!(message("h2 d3 dead=d3"), "♦️3 is used twice");
+    }
+
+    #[test]
+    fn odds() {
+        let odds = "1A 2A 3A 4A 1K".parse::<Scenario>().unwrap().odds();
+        assert!(odds.win > 0.98);
+        let crowded = "1A 2A 3A 4A 1K n=6".parse::<Scenario>().unwrap().odds();
+        assert!(crowded.win < odds.win);
+    }
+}
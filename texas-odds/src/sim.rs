@@ -0,0 +1,546 @@
+//! Headless self-play between bots.
+//!
+//! The runner deals its own fixed-limit hold'em hands: blinds are 1/2 chips,
+//! bets are one big blind on preflop and flop and two big blinds on turn and
+//! river, with at most four bets per street. Stacks are unlimited, so every
+//! hand is played to the end and the result is measured in big blinds.
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+};
+
+use heapless::Vec as HeaplessVec;
+
+use crate::{
+    odds::{get_max_hand, Stage},
+    texas::{iter_all_cards, Card, CardNum},
+};
+
+pub const SMALL_BLIND: u32 = 1;
+pub const BIG_BLIND: u32 = 2;
+const MAX_BETS_PER_STREET: u32 = 4;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Fold,
+    /// Call the current bet, or check if there is nothing to call
+    Call,
+    /// Bet or raise one fixed bet size. Treated as a call once the street is capped.
+    Raise,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    fn bet_size(&self) -> u32 {
+        match self {
+            Street::Preflop | Street::Flop => BIG_BLIND,
+            Street::Turn | Street::River => BIG_BLIND * 2,
+        }
+    }
+}
+
+/// What a bot can see when it is its turn to act
+#[derive(Debug, Clone)]
+pub struct GameState<'a> {
+    /// Seat index of the acting bot, 0 is the button
+    pub seat: usize,
+    pub n_players: usize,
+    /// Players that have not folded, including the acting bot
+    pub n_active: usize,
+    pub hole_cards: [Card; 2],
+    pub pub_cards: &'a [Card],
+    pub street: Street,
+    pub pot: u32,
+    pub to_call: u32,
+    pub bet_size: u32,
+    pub can_raise: bool,
+}
+
+pub trait Bot {
+    fn name(&self) -> &str;
+    fn act(&mut self, state: &GameState) -> Action;
+}
+
+/// Never folds and never raises
+#[derive(Debug, Default)]
+pub struct AlwaysCall;
+
+impl Bot for AlwaysCall {
+    fn name(&self) -> &str {
+        "always-call"
+    }
+
+    fn act(&mut self, _state: &GameState) -> Action {
+        Action::Call
+    }
+}
+
+/// Picks an action uniformly at random
+#[derive(Debug)]
+pub struct RandomBot {
+    rng: SmallRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn act(&mut self, state: &GameState) -> Action {
+        match self.rng.gen_range(0..3) {
+            0 if state.to_call > 0 => Action::Fold,
+            2 => Action::Raise,
+            _ => Action::Call,
+        }
+    }
+}
+
+/// Raises, calls or folds by comparing `Stage::win_rate_with_n_players` against two thresholds.
+///
+/// Preflop odds are cached by starting hand class and number of players. The other streets are
+/// enumerated once per street and reused for the later decisions on it, an enumeration takes up
+/// to a second, which makes this bot orders of magnitude slower than the others.
+#[derive(Debug)]
+pub struct EquityThreshold {
+    call: f64,
+    raise: f64,
+    preflop_cache: HashMap<(CardNum, CardNum, bool, usize), f64>,
+    /// The equity of the last postflop spot
+    street_cache: Option<(Spot, f64)>,
+}
+
+/// Hole cards, community cards and number of active players
+type Spot = ([Card; 2], HeaplessVec<Card, 5>, usize);
+
+impl EquityThreshold {
+    pub fn new(call: f64, raise: f64) -> Self {
+        assert!(
+            call <= raise,
+            "call threshold must not exceed raise threshold"
+        );
+        Self {
+            call,
+            raise,
+            preflop_cache: HashMap::new(),
+            street_cache: None,
+        }
+    }
+
+    fn equity(&mut self, state: &GameState) -> f64 {
+        let n = state.n_active.max(2);
+        let calc = || {
+            let odds = Stage::new(state.hole_cards, state.pub_cards).win_rate_with_n_players(n);
+            odds.win + odds.tie / 2.
+        };
+        if !state.pub_cards.is_empty() {
+            let spot: Spot = (
+                state.hole_cards,
+                HeaplessVec::from_slice(state.pub_cards).unwrap(),
+                n,
+            );
+            return match &self.street_cache {
+                Some((cached, equity)) if *cached == spot => *equity,
+                _ => {
+                    let equity = calc();
+                    self.street_cache = Some((spot, equity));
+                    equity
+                }
+            };
+        }
+
+        let [a, b] = state.hole_cards;
+        let key = (a.num.max(b.num), a.num.min(b.num), a.suit == b.suit, n);
+        *self.preflop_cache.entry(key).or_insert_with(calc)
+    }
+}
+
+impl Default for EquityThreshold {
+    fn default() -> Self {
+        Self::new(0.3, 0.6)
+    }
+}
+
+impl Bot for EquityThreshold {
+    fn name(&self) -> &str {
+        "equity-threshold"
+    }
+
+    fn act(&mut self, state: &GameState) -> Action {
+        let equity = self.equity(state);
+        if equity >= self.raise {
+            Action::Raise
+        } else if equity >= self.call || state.to_call == 0 {
+            Action::Call
+        } else {
+            Action::Fold
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BotResult {
+    pub name: String,
+    pub hands: u64,
+    pub total_bb: f64,
+    pub bb_per_100: f64,
+    /// Half width of the 95% confidence interval of `bb_per_100`
+    pub ci95: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentResult {
+    pub results: Vec<BotResult>,
+}
+
+impl Display for TournamentResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for result in self.results.iter() {
+            writeln!(
+                f,
+                "{}: {:.2} ± {:.2} bb/100 ({} hands)",
+                result.name, result.bb_per_100, result.ci95, result.hands
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays seeded hands between a fixed table of bots, rotating the button every hand
+pub struct Tournament {
+    bots: Vec<Box<dyn Bot>>,
+    rng: SmallRng,
+    button: usize,
+    hands: u64,
+    sum: Vec<f64>,
+    sum_sq: Vec<f64>,
+}
+
+impl Tournament {
+    pub fn new(bots: Vec<Box<dyn Bot>>, seed: u64) -> Self {
+        assert!(
+            bots.len() >= 2 && bots.len() <= 9,
+            "Invalid number of bots {}",
+            bots.len()
+        );
+        let n = bots.len();
+        Self {
+            bots,
+            rng: SmallRng::seed_from_u64(seed),
+            button: 0,
+            hands: 0,
+            sum: vec![0.; n],
+            sum_sq: vec![0.; n],
+        }
+    }
+
+    pub fn run(&mut self, n_hands: u64) -> TournamentResult {
+        for _ in 0..n_hands {
+            self.play_hand();
+        }
+
+        self.result()
+    }
+
+    pub fn result(&self) -> TournamentResult {
+        let n = self.hands as f64;
+        TournamentResult {
+            results: self
+                .bots
+                .iter()
+                .enumerate()
+                .map(|(i, bot)| {
+                    let mean = if n > 0. { self.sum[i] / n } else { 0. };
+                    let var = if n > 1. {
+                        (self.sum_sq[i] - n * mean * mean) / (n - 1.)
+                    } else {
+                        0.
+                    };
+                    BotResult {
+                        name: bot.name().to_string(),
+                        hands: self.hands,
+                        total_bb: self.sum[i],
+                        bb_per_100: mean * 100.,
+                        ci95: 1.96 * (var.max(0.) / n.max(1.)).sqrt() * 100.,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Plays one hand and returns the chips won or lost by each bot
+    pub fn play_hand(&mut self) -> Vec<i64> {
+        let n = self.bots.len();
+        let mut deck: Vec<Card> = iter_all_cards().collect();
+        deck.shuffle(&mut self.rng);
+        // seats are relative to the button, `bot_of(seat)` maps them back to bots
+        let button = self.button;
+        let bot_of = |seat: usize| (button + seat) % n;
+        let hole_cards: Vec<[Card; 2]> = (0..n)
+            .map(|_| [deck.pop().unwrap(), deck.pop().unwrap()])
+            .collect();
+        let mut hand = HandState {
+            pub_cards: Vec::with_capacity(5),
+            committed: vec![0; n],
+            folded: vec![false; n],
+        };
+        let (sb, bb) = if n == 2 { (0, 1) } else { (1, 2) };
+        hand.committed[sb] = SMALL_BLIND;
+        hand.committed[bb] = BIG_BLIND;
+
+        for street in [Street::Preflop, Street::Flop, Street::Turn, Street::River] {
+            match street {
+                Street::Preflop => {}
+                Street::Flop => hand.pub_cards.extend(deck.drain(deck.len() - 3..)),
+                Street::Turn | Street::River => hand.pub_cards.push(deck.pop().unwrap()),
+            }
+            let first = match street {
+                Street::Preflop => (bb + 1) % n,
+                _ => 1,
+            };
+            self.betting_round(&mut hand, street, first, &hole_cards, &bot_of);
+            if hand.n_active() == 1 {
+                break;
+            }
+        }
+
+        let pot: u32 = hand.committed.iter().sum();
+        let mut winners: Vec<usize> = (0..n).filter(|&seat| !hand.folded[seat]).collect();
+        if winners.len() > 1 {
+            let hands: Vec<_> = winners
+                .iter()
+                .map(|&seat| {
+                    let mut cards = hand.pub_cards.clone();
+                    cards.extend_from_slice(&hole_cards[seat]);
+                    get_max_hand(&cards)
+                })
+                .collect();
+            let best = hands.iter().max().unwrap();
+            winners = winners
+                .iter()
+                .zip(hands.iter())
+                .filter(|(_, hand)| *hand == best)
+                .map(|(&seat, _)| seat)
+                .collect();
+        }
+
+        let mut deltas = vec![0i64; n];
+        for seat in 0..n {
+            deltas[bot_of(seat)] -= hand.committed[seat] as i64;
+        }
+        let share = pot / winners.len() as u32;
+        let odd_chips = pot % winners.len() as u32;
+        for (i, &seat) in winners.iter().enumerate() {
+            let extra = if (i as u32) < odd_chips { 1 } else { 0 };
+            deltas[bot_of(seat)] += (share + extra) as i64;
+        }
+
+        for (i, delta) in deltas.iter().enumerate() {
+            let bb = *delta as f64 / BIG_BLIND as f64;
+            self.sum[i] += bb;
+            self.sum_sq[i] += bb * bb;
+        }
+        self.hands += 1;
+        self.button = (self.button + 1) % n;
+        deltas
+    }
+
+    fn betting_round(
+        &mut self,
+        hand: &mut HandState,
+        street: Street,
+        first: usize,
+        hole_cards: &[[Card; 2]],
+        bot_of: &impl Fn(usize) -> usize,
+    ) {
+        let n = self.bots.len();
+        let bet_size = street.bet_size();
+        let mut street_bet = vec![0u32; n];
+        let mut current_bet = 0;
+        let mut n_bets = 0;
+        if street == Street::Preflop {
+            street_bet.copy_from_slice(&hand.committed);
+            current_bet = BIG_BLIND;
+            n_bets = 1;
+        }
+
+        // number of players that still have to act before the street is closed
+        let mut pending = hand.n_active();
+        let mut seat = first;
+        while pending > 0 && hand.n_active() > 1 {
+            if hand.folded[seat] {
+                seat = (seat + 1) % n;
+                continue;
+            }
+
+            let to_call = current_bet - street_bet[seat];
+            let can_raise = n_bets < MAX_BETS_PER_STREET;
+            let state = GameState {
+                seat,
+                n_players: n,
+                n_active: hand.n_active(),
+                hole_cards: hole_cards[seat],
+                pub_cards: &hand.pub_cards,
+                street,
+                pot: hand.committed.iter().sum(),
+                to_call,
+                bet_size,
+                can_raise,
+            };
+            let action = self.bots[bot_of(seat)].act(&state);
+            match action {
+                Action::Fold if to_call > 0 => {
+                    hand.folded[seat] = true;
+                    pending -= 1;
+                }
+                Action::Raise if can_raise => {
+                    let amount = to_call + bet_size;
+                    street_bet[seat] += amount;
+                    hand.committed[seat] += amount;
+                    current_bet = street_bet[seat];
+                    n_bets += 1;
+                    pending = hand.n_active() - 1;
+                }
+                _ => {
+                    street_bet[seat] += to_call;
+                    hand.committed[seat] += to_call;
+                    pending -= 1;
+                }
+            }
+            seat = (seat + 1) % n;
+        }
+    }
+}
+
+impl Debug for Tournament {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tournament")
+            .field(
+                "bots",
+                &self.bots.iter().map(|x| x.name()).collect::<Vec<_>>(),
+            )
+            .field("hands", &self.hands)
+            .finish()
+    }
+}
+
+struct HandState {
+    pub_cards: Vec<Card>,
+    /// Chips put into the pot by each seat during the whole hand
+    committed: Vec<u32>,
+    folded: Vec<bool>,
+}
+
+impl HandState {
+    fn n_active(&self) -> usize {
+        self.folded.iter().filter(|x| !**x).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Action, AlwaysCall, Bot, EquityThreshold, GameState, RandomBot, Street, Tournament,
+    };
+    use crate::{odds::Stage, texas::Card};
+
+    struct AlwaysFold;
+
+    impl Bot for AlwaysFold {
+        fn name(&self) -> &str {
+            "always-fold"
+        }
+
+        fn act(&mut self, _state: &GameState) -> Action {
+            Action::Fold
+        }
+    }
+
+    struct AlwaysRaise;
+
+    impl Bot for AlwaysRaise {
+        fn name(&self) -> &str {
+            "always-raise"
+        }
+
+        fn act(&mut self, _state: &GameState) -> Action {
+            Action::Raise
+        }
+    }
+
+    #[test]
+    fn zero_sum() {
+        let mut tournament = Tournament::new(
+            vec![
+                Box::new(AlwaysCall),
+                Box::new(RandomBot::new(1)),
+                Box::new(RandomBot::new(2)),
+            ],
+            42,
+        );
+        for _ in 0..2000 {
+            assert_eq!(tournament.play_hand().iter().sum::<i64>(), 0);
+        }
+        let result = tournament.result();
+        let total: f64 = result.results.iter().map(|x| x.total_bb).sum();
+        assert!(total.abs() < 1e-9);
+    }
+
+    #[test]
+    fn same_seed_same_result() {
+        let run = || {
+            Tournament::new(vec![Box::new(RandomBot::new(7)), Box::new(AlwaysCall)], 3)
+                .run(1000)
+                .results[0]
+                .total_bb
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn equity_is_cached_per_street() {
+        let hole_cards = [Card::from("sA"), Card::from("cA")];
+        let board: Vec<Card> = ["hA", "dA", "c7"].map(Card::from).to_vec();
+        let state = GameState {
+            seat: 0,
+            n_players: 3,
+            n_active: 3,
+            hole_cards,
+            pub_cards: &board,
+            street: Street::Flop,
+            pot: 6,
+            to_call: 2,
+            bet_size: 2,
+            can_raise: true,
+        };
+        let mut bot = EquityThreshold::default();
+        let equity = bot.equity(&state);
+        let odds = Stage::new(hole_cards, &board).win_rate_with_n_players(3);
+        assert_eq!(equity, odds.win + odds.tie / 2.);
+        assert!(bot.street_cache.is_some());
+        assert_eq!(bot.act(&state), Action::Raise);
+    }
+
+    #[test]
+    fn folding_loses_the_blinds() {
+        let result = Tournament::new(vec![Box::new(AlwaysFold), Box::new(AlwaysRaise)], 0).run(100);
+        // the small blind is folded on the button, the big blind is folded to a raise
+        assert_eq!(result.results[0].total_bb, -50. * 0.5 - 50. * 1.);
+        assert_eq!(result.results[0].bb_per_100, -75.);
+    }
+}
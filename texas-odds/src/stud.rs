@@ -0,0 +1,283 @@
+//! Seven-card Stud and Razz, where every player has their own up and down cards and there is no board.
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt::{Debug, Display};
+use heapless::Vec as HeaplessVec;
+use rand::{rngs::SmallRng, SeedableRng};
+
+use crate::{
+    low::{get_best_low, LowRule},
+    odds::{draw, get_max_hand, Odds, DEFAULT_ITERATIONS},
+    texas::{iter_all_cards, Card, HandType},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StudGame {
+    /// Best five card high hand
+    Stud,
+    /// Best five card A-5 low hand
+    Razz,
+}
+
+#[derive(Debug)]
+pub struct StudStage {
+    game: StudGame,
+    /// Our down and up cards
+    my_cards: HeaplessVec<Card, 7>,
+    /// The visible up cards of every opponent still in the hand
+    opponents_up: Vec<HeaplessVec<Card, 4>>,
+    /// Exposed cards that are out of play, e.g. the up cards of folded players
+    dead_cards: Vec<Card>,
+}
+
+impl Display for StudStage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} my_cards: {:?}, opponents: {:?}",
+            self.game, self.my_cards, self.opponents_up
+        )
+    }
+}
+
+impl StudStage {
+    /// `my_cards` are all our cards, down and up. Every opponent has been dealt as many
+    /// cards as we have, `opponents_up` are the ones we can see.
+    pub fn new(game: StudGame, my_cards: &[Card], opponents_up: &[&[Card]]) -> Self {
+        assert!(
+            my_cards.len() >= 3 && my_cards.len() <= 7,
+            "Invalid my_cards length {}",
+            my_cards.len()
+        );
+        assert!(!opponents_up.is_empty(), "at least one opponent is needed");
+        for up in opponents_up {
+            assert!(
+                up.len() <= 4 && up.len() < my_cards.len(),
+                "Invalid up cards length {}",
+                up.len()
+            );
+        }
+        let stage = Self {
+            game,
+            my_cards: HeaplessVec::from_slice(my_cards).unwrap(),
+            opponents_up: opponents_up
+                .iter()
+                .map(|x| HeaplessVec::from_slice(x).unwrap())
+                .collect(),
+            dead_cards: Vec::new(),
+        };
+        let known = stage.known_cards();
+        for (i, card) in known.iter().enumerate() {
+            assert!(!known[..i].contains(card), "{card} is already dealt");
+        }
+        assert!(
+            stage.cards_needed() <= 52 - stage.known_cards().len(),
+            "Not enough cards for {} players",
+            opponents_up.len() + 1
+        );
+        stage
+    }
+
+    /// Excludes `dead_cards`, the folded up cards of other players, from the deals
+    pub fn with_dead_cards(mut self, dead_cards: &[Card]) -> Self {
+        for (i, card) in dead_cards.iter().enumerate() {
+            assert!(
+                !self.known_cards().contains(card) && !dead_cards[..i].contains(card),
+                "{card} is already dealt"
+            );
+        }
+        self.dead_cards = dead_cards.to_vec();
+        assert!(
+            self.cards_needed() <= 52 - self.known_cards().len(),
+            "Not enough cards for {} players and {} dead cards",
+            self.opponents_up.len() + 1,
+            dead_cards.len()
+        );
+        self
+    }
+
+    fn known_cards(&self) -> Vec<Card> {
+        self.my_cards
+            .iter()
+            .chain(self.opponents_up.iter().flatten())
+            .chain(self.dead_cards.iter())
+            .copied()
+            .collect()
+    }
+
+    /// Unknown cards dealt in one runout: opponents' down cards and every remaining street
+    fn cards_needed(&self) -> usize {
+        self.opponents_up.iter().map(|x| 7 - x.len()).sum::<usize>() + 7 - self.my_cards.len()
+    }
+
+    /// Odds estimated with `DEFAULT_ITERATIONS` simulated runouts
+    pub fn win_rate(&self) -> Odds {
+        self.simulate(DEFAULT_ITERATIONS, 0)
+    }
+
+    /// Odds over `iterations` deals of the unknown cards, drawn from a deck seeded with `seed`
+    ///
+    /// `hand_rate` is the distribution of our final high hand, it is empty for Razz.
+    pub fn simulate(&self, iterations: usize, seed: u64) -> Odds {
+        let known = self.known_cards();
+        let mut deck: Vec<Card> = iter_all_cards().filter(|x| !known.contains(x)).collect();
+        let needed = self.cards_needed();
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut wins = 0;
+        let mut ties = 0;
+        let mut hand_count: BTreeMap<HandType, usize> = BTreeMap::new();
+        for _ in 0..iterations {
+            let mut drawn = draw(&mut deck, needed, &mut rng).iter().copied();
+            let mut mine = self.my_cards.clone();
+            mine.extend(drawn.by_ref().take(7 - self.my_cards.len()));
+            let others: Vec<HeaplessVec<Card, 7>> = self
+                .opponents_up
+                .iter()
+                .map(|up| {
+                    let mut cards: HeaplessVec<Card, 7> = up.iter().copied().collect();
+                    cards.extend(drawn.by_ref().take(7 - up.len()));
+                    cards
+                })
+                .collect();
+
+            let ord = match self.game {
+                StudGame::Stud => {
+                    let hand = get_max_hand(&mine);
+                    *hand_count.entry(hand.hand_type()).or_insert(0) += 1;
+                    others
+                        .iter()
+                        .map(|x| get_max_hand(x).cmp(&hand))
+                        .max()
+                        .unwrap()
+                }
+                StudGame::Razz => {
+                    let low = get_best_low(&mine, LowRule::AceToFive);
+                    others
+                        .iter()
+                        .map(|x| get_best_low(x, LowRule::AceToFive).cmp(&low))
+                        .max()
+                        .unwrap()
+                }
+            };
+            match ord {
+                core::cmp::Ordering::Less => wins += 1,
+                core::cmp::Ordering::Equal => ties += 1,
+                core::cmp::Ordering::Greater => {}
+            }
+        }
+
+        Odds {
+            win: wins as f64 / iterations as f64,
+            tie: ties as f64 / iterations as f64,
+            hand_rate: hand_count
+                .into_iter()
+                .map(|(hand, count)| (hand, count as f64 / iterations as f64))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use super::{StudGame, StudStage};
+    use crate::texas::{Card, HandType};
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split_whitespace().map(|x| x.into()).collect()
+    }
+
+    #[test]
+    fn stud() {
+        // rolled up aces against a king up card
+        let stage = StudStage::new(StudGame::Stud, &cards("1A 2A 3A"), &[&cards("4K")]);
+        let odds = stage.simulate(5000, 1);
+        assert!(odds.win > 0.8);
+        assert_eq!(odds.win, stage.simulate(5000, 1).win);
+        assert!(odds.hand_rate[&HandType::ThreeOfAKind] > 0.);
+        assert!(!odds.hand_rate.contains_key(&HandType::Pair));
+
+        // seventh street: every card is known but the opponent's three down cards
+        let stage = StudStage::new(
+            StudGame::Stud,
+            &cards("1A 2A 3A 4A 12 23 34"),
+            &[&cards("1K 2K 3K 45")],
+        );
+        let odds = stage.simulate(2000, 1);
+        assert_eq!(odds.win, 1.);
+    }
+
+    #[test]
+    fn razz() {
+        // a made wheel can't lose
+        let stage = StudStage::new(
+            StudGame::Razz,
+            &cards("1A 22 33 44 15 1K 2K"),
+            &[&cards("16 27 38 49")],
+        );
+        assert_eq!(
+            stage.simulate(1000, 1).win + stage.simulate(1000, 1).tie,
+            1.
+        );
+
+        // A-2-3 against a king showing is a big favourite
+        let stage = StudStage::new(StudGame::Razz, &cards("1A 22 33"), &[&cards("4K")]);
+        let odds = stage.simulate(5000, 1);
+        assert!(odds.win > 0.75);
+        assert!(odds.hand_rate.is_empty());
+    }
+
+    #[test]
+    fn dead_cards() {
+        // four hearts on fifth street
+        let stage = StudStage::new(
+            StudGame::Stud,
+            &cards("1A 1K 1Q 1J 42"),
+            &[&cards("23 34 45")],
+        );
+        assert!(stage.simulate(2000, 1).hand_rate[&HandType::Flush] > 0.);
+        // every other heart has been folded
+        let stage = stage.with_dead_cards(&cards("12 13 14 15 16 17 18 19 1T"));
+        let odds = stage.simulate(2000, 1);
+        assert!(!odds.hand_rate.contains_key(&HandType::Flush));
+        assert!(!odds.hand_rate.contains_key(&HandType::StraightFlush));
+    }
+
+    /// One up card for each of `n` opponents
+    fn up_cards(n: usize) -> Vec<Vec<Card>> {
+        cards("1K 2K 3K 4K 1Q 2Q 3Q 4Q")[..n]
+            .iter()
+            .map(|x| vec![*x])
+            .collect()
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough cards for 9 players")]
+    fn too_many_players() {
+        let up = up_cards(8);
+        let up: Vec<&[Card]> = up.iter().map(|x| x.as_slice()).collect();
+        StudStage::new(StudGame::Stud, &cards("1A 2A 3A"), &up);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough cards for 7 players and 4 dead cards")]
+    fn too_many_dead_cards() {
+        let up = up_cards(6);
+        let up: Vec<&[Card]> = up.iter().map(|x| x.as_slice()).collect();
+        StudStage::new(StudGame::Stud, &cards("1A 2A 3A"), &up)
+            .with_dead_cards(&cards("12 13 14 15"));
+    }
+
+    #[test]
+    #[should_panic(expected = "is already dealt")]
+    fn duplicate_up_cards() {
+        StudStage::new(StudGame::Stud, &cards("1A 2A 3A"), &[&cards("1A")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "is already dealt")]
+    fn duplicate_dead_cards() {
+        StudStage::new(StudGame::Stud, &cards("1A 2A 3A"), &[&cards("4K")])
+            .with_dead_cards(&cards("12 12"));
+    }
+}
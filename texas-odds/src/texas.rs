@@ -1,10 +1,13 @@
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+use core::fmt::{Debug, Display};
 use heapless::Vec;
-use std::fmt::{Debug, Display};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Suit {
     Heart,
     Diamond,
@@ -14,6 +17,7 @@ pub enum Suit {
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HandType {
     /// 高牌
     HighCard,
@@ -38,7 +42,7 @@ pub enum HandType {
 }
 
 impl Display for HandType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             HandType::HighCard => write!(f, "HighCard"),
             HandType::Pair => write!(f, "Pair"),
@@ -54,6 +58,51 @@ impl Display for HandType {
     }
 }
 
+/// The deck and ranking rules of the game
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub enum Variant {
+    /// 52 cards, regular hold'em rankings
+    #[default]
+    Standard,
+    /// 短牌. 36 cards without 2-5, a flush beats a full house and A-6-7-8-9 is the lowest straight
+    ShortDeck,
+}
+
+impl Variant {
+    /// The lowest card number in the deck
+    pub fn lowest(&self) -> CardNum {
+        match self {
+            Variant::Standard => CardNum::Two,
+            Variant::ShortDeck => CardNum::Six,
+        }
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        card.num >= self.lowest()
+    }
+
+    pub fn deck_size(&self) -> usize {
+        match self {
+            Variant::Standard => 52,
+            Variant::ShortDeck => 36,
+        }
+    }
+
+    /// Strength of the hand type under this variant's rules, higher is better
+    pub fn hand_type_rank(&self, hand: HandType) -> u8 {
+        match (self, hand) {
+            (Variant::ShortDeck, HandType::Flush) => HandType::FullHouse as u8,
+            (Variant::ShortDeck, HandType::FullHouse) => HandType::Flush as u8,
+            (_, hand) => hand as u8,
+        }
+    }
+
+    pub fn cmp_hand_type(&self, a: HandType, b: HandType) -> core::cmp::Ordering {
+        self.hand_type_rank(a).cmp(&self.hand_type_rank(b))
+    }
+}
+
 impl HandType {
     pub const ALL: [HandType; 10] = [
         HandType::HighCard,
@@ -69,14 +118,14 @@ impl HandType {
     ];
 
     /// cards should be sorted by number
-    fn is_royal_flush(cards: &[Card]) -> bool {
-        Self::is_strait_flush(cards) && cards[0].num == CardNum::Ten
+    fn is_royal_flush(cards: &[Card], variant: Variant) -> bool {
+        Self::is_strait_flush(cards, variant) && cards[0].num == CardNum::Ten
     }
 
     /// cards should be sorted by number
-    fn is_strait_flush(cards: &[Card]) -> bool {
+    fn is_strait_flush(cards: &[Card], variant: Variant) -> bool {
         debug_assert_eq!(cards.len(), 5);
-        Self::is_strait(cards) && Self::is_flush(cards)
+        Self::is_strait(cards, variant) && Self::is_flush(cards)
     }
 
     fn is_flush(cards: &[Card]) -> bool {
@@ -88,7 +137,7 @@ impl HandType {
     }
 
     /// cards should be sorted by number
-    fn is_strait(cards: &[Card]) -> bool {
+    fn is_strait(cards: &[Card], variant: Variant) -> bool {
         debug_assert_eq!(cards.len(), 5);
         let mut success = true;
         let mut last = cards[0];
@@ -100,7 +149,7 @@ impl HandType {
             last = *card;
         }
 
-        if !success && cards[4].num == CardNum::Ace && cards[0].num == CardNum::Two {
+        if !success && cards[4].num == CardNum::Ace && cards[0].num == variant.lowest() {
             success = true;
             let mut last = cards[0];
             for card in cards.iter().skip(1).take(3) {
@@ -115,6 +164,17 @@ impl HandType {
         success
     }
 
+    /// Straight cards from high to low. The ace of an ace-low straight counts as the lowest card.
+    ///
+    /// cards should be sorted by number
+    fn strait_cmp_cards(cards: &[Card]) -> Vec<CardNum, 5> {
+        let mut ans: Vec<CardNum, 5> = cards.iter().rev().map(|x| x.num).collect();
+        if ans[0] == CardNum::Ace && ans[1] != CardNum::King {
+            ans.rotate_left(1);
+        }
+        ans
+    }
+
     /// cards should be sorted by number
     #[allow(clippy::nonminimal_bool)]
     fn is_full_house(cards: &[Card]) -> bool {
@@ -165,7 +225,7 @@ impl HandType {
                 }
             }
 
-            ans.sort();
+            ans.sort_unstable();
             ans.reverse();
             ans.insert(0, cards[2].num).unwrap();
             Some(ans)
@@ -179,21 +239,21 @@ impl HandType {
         if cards[0].num == cards[1].num && cards[2].num == cards[3].num {
             let mut vec = Vec::new();
             vec.extend([cards[0].num, cards[2].num]);
-            vec.sort_by_key(|x| -isize::from(*x));
+            vec.sort_unstable_by(|a, b| b.cmp(a));
             vec.push(cards[4].num).unwrap();
             return Some(vec);
         }
         if cards[0].num == cards[1].num && cards[3].num == cards[4].num {
             let mut vec = Vec::new();
             vec.extend([cards[0].num, cards[3].num]);
-            vec.sort_by_key(|x| -isize::from(*x));
+            vec.sort_unstable_by(|a, b| b.cmp(a));
             vec.push(cards[2].num).unwrap();
             return Some(vec);
         }
         if cards[1].num == cards[2].num && cards[3].num == cards[4].num {
             let mut vec = Vec::new();
             vec.extend([cards[1].num, cards[3].num]);
-            vec.sort_by_key(|x| -isize::from(*x));
+            vec.sort_unstable_by(|a, b| b.cmp(a));
             vec.push(cards[0].num).unwrap();
             return Some(vec);
         }
@@ -231,8 +291,9 @@ impl HandType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CardNum {
     Two,
     Three,
@@ -293,25 +354,29 @@ impl From<usize> for CardNum {
 
 impl From<&str> for CardNum {
     fn from(value: &str) -> Self {
-        match value {
-            "1" | "A" | "a" => CardNum::Ace,
-            "2" => CardNum::Two,
-            "3" => CardNum::Three,
-            "4" => CardNum::Four,
-            "5" => CardNum::Five,
-            "6" => CardNum::Six,
-            "7" => CardNum::Seven,
-            "8" => CardNum::Eight,
-            "9" => CardNum::Nine,
-            "10" | "T" | "t" => CardNum::Ten,
-            "11" | "J" | "j" => CardNum::Jack,
-            "12" | "Q" | "q" => CardNum::Queen,
-            "13" | "K" | "k" => CardNum::King,
-            x => panic!("Invalid card type {x}"),
-        }
+        parse_num(value).unwrap_or_else(|| panic!("Invalid card type {value}"))
     }
 }
 
+fn parse_num(value: &str) -> Option<CardNum> {
+    Some(match value {
+        "1" | "A" | "a" => CardNum::Ace,
+        "2" => CardNum::Two,
+        "3" => CardNum::Three,
+        "4" => CardNum::Four,
+        "5" => CardNum::Five,
+        "6" => CardNum::Six,
+        "7" => CardNum::Seven,
+        "8" => CardNum::Eight,
+        "9" => CardNum::Nine,
+        "10" | "T" | "t" => CardNum::Ten,
+        "11" | "J" | "j" => CardNum::Jack,
+        "12" | "Q" | "q" => CardNum::Queen,
+        "13" | "K" | "k" => CardNum::King,
+        _ => return None,
+    })
+}
+
 impl From<CardNum> for usize {
     fn from(value: CardNum) -> Self {
         match value {
@@ -351,7 +416,7 @@ impl From<usize> for Suit {
 }
 
 impl Display for Suit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Suit::Heart => f.write_str("♥️"),
             Suit::Diamond => f.write_str("♦️"),
@@ -362,7 +427,7 @@ impl Display for Suit {
 }
 
 impl Display for CardNum {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             CardNum::Ace => f.write_str("A"),
             CardNum::Two => f.write_str("2"),
@@ -382,6 +447,7 @@ impl Display for CardNum {
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Card {
     pub suit: Suit,
     pub num: CardNum,
@@ -391,6 +457,7 @@ pub struct Card {
 pub struct Hand {
     hand: HandType,
     hand_cmp_cards: Vec<CardNum, 5>,
+    variant: Variant,
 }
 
 impl PartialEq for Hand {
@@ -402,19 +469,20 @@ impl PartialEq for Hand {
 impl Eq for Hand {}
 
 impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.hand.partial_cmp(&other.hand) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        debug_assert_eq!(self.hand_cmp_cards.len(), other.hand_cmp_cards.len());
-        Some(self.hand_cmp_cards.cmp(&other.hand_cmp_cards))
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        debug_assert_eq!(self.variant, other.variant);
+        match self.variant.cmp_hand_type(self.hand, other.hand) {
+            core::cmp::Ordering::Equal => {}
+            ord => return ord,
+        }
+        debug_assert_eq!(self.hand_cmp_cards.len(), other.hand_cmp_cards.len());
+        self.hand_cmp_cards.cmp(&other.hand_cmp_cards)
     }
 }
 
@@ -424,6 +492,21 @@ impl Hand {
         self.hand
     }
 
+    #[inline(always)]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// A number that orders the hands of the same variant like `Ord`, higher is better
+    pub fn rank(&self) -> u32 {
+        let mut rank = self.variant.hand_type_rank(self.hand) as u32;
+        for i in 0..5 {
+            let num = self.hand_cmp_cards.get(i).map_or(0, |x| *x as u32 + 1);
+            rank = rank << 4 | num;
+        }
+        rank
+    }
+
     #[inline(always)]
     #[allow(dead_code)]
     pub(crate) fn cmp_cards(&self) -> &[CardNum] {
@@ -431,74 +514,106 @@ impl Hand {
     }
 }
 
-pub fn calc_hand(cards: &[Card]) -> Hand {
-    let mut cards: Vec<Card, 7> = Vec::from_slice(cards).unwrap();
-    cards.sort_by_key(|x| x.num);
-    if HandType::is_royal_flush(&cards) {
-        Hand {
-            hand: HandType::RoyalFlush,
-            hand_cmp_cards: Vec::new(),
+/// Card numbers separated by spaces, written without allocating
+struct Spaced<'a>(&'a [CardNum]);
+
+impl Display for Spaced<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, num) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{num}")?;
         }
-    } else if HandType::is_strait_flush(&cards) {
-        cards.reverse();
-        Hand {
-            hand: HandType::StraightFlush,
-            hand_cmp_cards: cards.iter().map(|x| x.num).collect(),
+        Ok(())
+    }
+}
+
+/// Describes the hand, e.g. `full house, K full of 2` or `pair of 9, kickers A Q 4`
+impl Display for Hand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let cards = &self.hand_cmp_cards;
+        let join = Spaced;
+        match self.hand {
+            HandType::HighCard => {
+                write!(f, "high card {}, kickers {}", cards[0], join(&cards[1..]))
+            }
+            HandType::Pair => write!(f, "pair of {}, kickers {}", cards[0], join(&cards[1..])),
+            HandType::TwoPair => write!(
+                f,
+                "two pair {} and {}, kicker {}",
+                cards[0], cards[1], cards[2]
+            ),
+            HandType::ThreeOfAKind => write!(
+                f,
+                "three of a kind {}, kickers {}",
+                cards[0],
+                join(&cards[1..])
+            ),
+            HandType::Straight => write!(f, "straight, {} high", cards[0]),
+            HandType::Flush => write!(f, "flush, {}", join(cards)),
+            HandType::FullHouse => write!(f, "full house, {} full of {}", cards[0], cards[1]),
+            HandType::FourOfAKind => write!(f, "four of a kind {}, kicker {}", cards[0], cards[1]),
+            HandType::StraightFlush => write!(f, "straight flush, {} high", cards[0]),
+            HandType::RoyalFlush => write!(f, "royal flush"),
         }
+    }
+}
+
+pub fn calc_hand(cards: &[Card]) -> Hand {
+    calc_hand_with_variant(cards, Variant::Standard)
+}
+
+pub fn calc_hand_with_variant(cards: &[Card], variant: Variant) -> Hand {
+    let mut cards: Vec<Card, 7> = Vec::from_slice(cards).unwrap();
+    cards.sort_unstable_by_key(|x| x.num);
+    let (hand, hand_cmp_cards) = if HandType::is_royal_flush(&cards, variant) {
+        (HandType::RoyalFlush, Vec::new())
+    } else if HandType::is_strait_flush(&cards, variant) {
+        (HandType::StraightFlush, HandType::strait_cmp_cards(&cards))
     } else if let Some(cmp) = HandType::four_of_a_kind(&cards) {
-        Hand {
-            hand: HandType::FourOfAKind,
-            hand_cmp_cards: cmp,
-        }
+        (HandType::FourOfAKind, cmp)
     } else if HandType::is_full_house(&cards) {
-        cards.reverse();
-        Hand {
-            hand: HandType::FullHouse,
-            hand_cmp_cards: cards.iter().map(|x| x.num).collect(),
-        }
+        // the middle card always belongs to the three of a kind
+        let pair = if cards[0].num == cards[2].num {
+            cards[4].num
+        } else {
+            cards[0].num
+        };
+        (
+            HandType::FullHouse,
+            Vec::from_slice(&[cards[2].num, pair]).unwrap(),
+        )
     } else if HandType::is_flush(&cards) {
-        Hand {
-            hand: HandType::Flush,
-            hand_cmp_cards: cards.iter().map(|x| x.num).collect(),
-        }
-    } else if HandType::is_strait(&cards) {
         cards.reverse();
-        Hand {
-            hand: HandType::Straight,
-            hand_cmp_cards: cards.iter().map(|x| x.num).collect(),
-        }
+        (HandType::Flush, cards.iter().map(|x| x.num).collect())
+    } else if HandType::is_strait(&cards, variant) {
+        (HandType::Straight, HandType::strait_cmp_cards(&cards))
     } else if let Some(cmp) = HandType::three_of_a_kind(&cards) {
-        Hand {
-            hand: HandType::ThreeOfAKind,
-            hand_cmp_cards: cmp,
-        }
+        (HandType::ThreeOfAKind, cmp)
     } else if let Some(cmp) = HandType::two_pair(&cards) {
-        Hand {
-            hand: HandType::TwoPair,
-            hand_cmp_cards: cmp,
-        }
+        (HandType::TwoPair, cmp)
     } else if let Some(cmp) = HandType::pair(&cards) {
-        Hand {
-            hand: HandType::Pair,
-            hand_cmp_cards: cmp,
-        }
+        (HandType::Pair, cmp)
     } else {
         cards.reverse();
-        Hand {
-            hand: HandType::HighCard,
-            hand_cmp_cards: cards.iter().map(|x| x.num).collect(),
-        }
+        (HandType::HighCard, cards.iter().map(|x| x.num).collect())
+    };
+    Hand {
+        hand,
+        hand_cmp_cards,
+        variant,
     }
 }
 
 impl Display for Card {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}{}", self.suit, self.num)
     }
 }
 
 impl Debug for Card {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}{}", self.suit, self.num)
     }
 }
@@ -512,50 +627,102 @@ impl PartialEq for Card {
 impl Eq for Card {}
 
 impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         match self.num.cmp(&other.num) {
-            std::cmp::Ordering::Equal => {}
+            core::cmp::Ordering::Equal => {}
             x => return Some(x),
         }
 
         if self.suit == other.suit {
-            return Some(std::cmp::Ordering::Equal);
+            return Some(core::cmp::Ordering::Equal);
         }
 
         None
     }
 }
 
+#[cfg(feature = "alloc")]
 impl From<&str> for Card {
     fn from(value: &str) -> Self {
-        match &value[0..1] {
-            "1" | "H" | "h" => Card {
-                suit: Suit::Heart,
-                num: CardNum::from(&value[1..]),
-            },
-            "2" | "D" | "d" => Card {
-                suit: Suit::Diamond,
-                num: CardNum::from(&value[1..]),
-            },
-            "3" | "C" | "c" => Card {
-                suit: Suit::Club,
-                num: CardNum::from(&value[1..]),
-            },
-            "4" | "S" | "s" => Card {
-                suit: Suit::Spade,
-                num: CardNum::from(&value[1..]),
-            },
-            _ => {
-                panic!("Invalid card {value}")
-            }
-        }
+        value.parse().unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+/// A card that isn't written as a suit followed by a number, e.g. `h2`, `1A` or `d13`
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCardError(pub String);
+
+#[cfg(feature = "alloc")]
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Invalid card {}", self.0)
     }
 }
 
+#[cfg(feature = "alloc")]
+impl core::error::Error for ParseCardError {}
+
+#[cfg(feature = "alloc")]
+impl core::str::FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCardError(value.to_string());
+        let suit = match value.get(0..1).ok_or_else(err)? {
+            "1" | "H" | "h" => Suit::Heart,
+            "2" | "D" | "d" => Suit::Diamond,
+            "3" | "C" | "c" => Suit::Club,
+            "4" | "S" | "s" => Suit::Spade,
+            _ => return Err(err()),
+        };
+        let num = parse_num(&value[1..]).ok_or_else(err)?;
+        Ok(Card { suit, num })
+    }
+}
+
+/// Parses the rank first notation of hand histories and ranges: `Ah`, `Td`, `10c`
+pub fn parse_card(s: &str) -> Option<Card> {
+    let (i, _) = s.char_indices().next_back()?;
+    let (num, suit) = s.split_at(i);
+    let suit = match suit {
+        "h" | "H" => Suit::Heart,
+        "d" | "D" => Suit::Diamond,
+        "c" | "C" => Suit::Club,
+        "s" | "S" => Suit::Spade,
+        _ => return None,
+    };
+    let num = match num {
+        "A" | "a" => CardNum::Ace,
+        "K" | "k" => CardNum::King,
+        "Q" | "q" => CardNum::Queen,
+        "J" | "j" => CardNum::Jack,
+        "T" | "t" | "10" => CardNum::Ten,
+        "9" => CardNum::Nine,
+        "8" => CardNum::Eight,
+        "7" => CardNum::Seven,
+        "6" => CardNum::Six,
+        "5" => CardNum::Five,
+        "4" => CardNum::Four,
+        "3" => CardNum::Three,
+        "2" => CardNum::Two,
+        _ => return None,
+    };
+    Some(Card { suit, num })
+}
+
 pub fn iter_all_cards() -> impl Iterator<Item = Card> {
+    iter_all_cards_with_variant(Variant::Standard)
+}
+
+pub fn iter_all_cards_with_variant(variant: Variant) -> impl Iterator<Item = Card> {
+    iter_standard_deck().filter(move |x| variant.contains(x))
+}
+
+fn iter_standard_deck() -> impl Iterator<Item = Card> {
     let mut suit = 1;
     let mut num = 1;
-    std::iter::from_fn(move || {
+    core::iter::from_fn(move || {
         if suit == 5 {
             None
         } else {
@@ -577,9 +744,31 @@ pub fn iter_all_cards() -> impl Iterator<Item = Card> {
 
 #[cfg(test)]
 mod test {
-    use crate::texas::{calc_hand, Card, CardNum, HandType, Suit};
+    use std::string::ToString;
 
-    use super::iter_all_cards;
+    use crate::texas::{calc_hand, calc_hand_with_variant, Card, CardNum, HandType, Suit, Variant};
+
+    use super::{iter_all_cards, iter_all_cards_with_variant};
+
+    #[test]
+    fn parse_card() {
+        assert_eq!("h2".parse::<Card>(), Ok(Card::from("12")));
+        assert_eq!("sT".parse::<Card>(), Ok(Card::from("410")));
+        assert_eq!("d13".parse::<Card>().unwrap().num, CardNum::King);
+        for invalid in ["", "h", "x2", "h14", "hX", "我2"] {
+            assert_eq!(
+                invalid.parse::<Card>().unwrap_err().to_string(),
+                format!("Invalid card {invalid}")
+            );
+        }
+
+        // rank first
+        assert_eq!(super::parse_card("Ah"), Some("hA".into()));
+        assert_eq!(super::parse_card("Td"), Some("d10".into()));
+        assert_eq!(super::parse_card("2c"), Some("c2".into()));
+        assert_eq!(super::parse_card("1x"), None);
+        assert_eq!(super::parse_card("A♥"), None);
+    }
 
     #[test]
     fn test_ord() {
@@ -588,6 +777,39 @@ mod test {
         assert!(HandType::RoyalFlush > HandType::HighCard);
     }
 
+    #[test]
+    fn test_hand_ord() {
+        let pair = calc_hand(&[
+            "11".into(),
+            "21".into(),
+            "37".into(),
+            "45".into(),
+            "12".into(),
+        ]);
+        let two_pair = calc_hand(&[
+            "113".into(),
+            "213".into(),
+            "37".into(),
+            "47".into(),
+            "12".into(),
+        ]);
+        let flush = calc_hand(&[
+            "12".into(),
+            "14".into(),
+            "16".into(),
+            "18".into(),
+            "113".into(),
+        ]);
+        let hands = [pair, two_pair, flush];
+        for (i, a) in hands.iter().enumerate() {
+            for (j, b) in hands.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j));
+                assert_eq!(a.partial_cmp(b), Some(i.cmp(&j)));
+            }
+        }
+        assert_eq!(hands.iter().max().unwrap().hand_type(), HandType::Flush);
+    }
+
     #[test]
     fn test_card() {
         let card: Card = "210".into();
@@ -840,6 +1062,22 @@ mod test {
         ];
         assert!(calc_hand(&cards_a) < calc_hand(&cards_b));
 
+        let cards_a = [
+            "113".into(),
+            "213".into(),
+            "37".into(),
+            "47".into(),
+            "12".into(),
+        ];
+        let cards_b = [
+            "11".into(),
+            "21".into(),
+            "37".into(),
+            "47".into(),
+            "12".into(),
+        ];
+        assert!(calc_hand(&cards_a) < calc_hand(&cards_b));
+
         let cards_a = [
             "211".into(),
             "311".into(),
@@ -857,6 +1095,147 @@ mod test {
         assert!(calc_hand(&cards_a) < calc_hand(&cards_b));
     }
 
+    #[test]
+    fn test_full_house() {
+        let kings_full = calc_hand(&[
+            "1K".into(),
+            "2K".into(),
+            "3K".into(),
+            "13".into(),
+            "23".into(),
+        ]);
+        let deuces_full = calc_hand(&[
+            "12".into(),
+            "22".into(),
+            "32".into(),
+            "1A".into(),
+            "2A".into(),
+        ]);
+        let kings_full_of_fours = calc_hand(&[
+            "1K".into(),
+            "2K".into(),
+            "4K".into(),
+            "14".into(),
+            "24".into(),
+        ]);
+        assert_eq!(kings_full.hand, HandType::FullHouse);
+        assert!(kings_full > deuces_full);
+        assert!(kings_full_of_fours > kings_full);
+        assert_eq!(kings_full.to_string(), "full house, K full of 3");
+        assert!(kings_full.rank() > deuces_full.rank());
+        assert!(kings_full_of_fours.rank() > kings_full.rank());
+    }
+
+    #[test]
+    fn rank() {
+        let hands: std::vec::Vec<super::Hand> = crate::odds::get_all_holdings(
+            &[
+                "1K".into(),
+                "27".into(),
+                "37".into(),
+                "12".into(),
+                "4Q".into(),
+            ],
+            &[],
+        )
+        .into_iter()
+        .map(|x| x.0)
+        .collect();
+        for pair in hands.windows(2) {
+            assert_eq!(pair[0].cmp(&pair[1]), pair[0].rank().cmp(&pair[1].rank()));
+        }
+    }
+
+    #[test]
+    fn test_low_straight() {
+        let wheel = calc_hand(&[
+            "21".into(),
+            "32".into(),
+            "43".into(),
+            "14".into(),
+            "25".into(),
+        ]);
+        let six_high = calc_hand(&[
+            "22".into(),
+            "33".into(),
+            "44".into(),
+            "15".into(),
+            "26".into(),
+        ]);
+        assert_eq!(wheel.hand, HandType::Straight);
+        assert!(wheel < six_high);
+
+        let flush_a = calc_hand(&[
+            "21".into(),
+            "22".into(),
+            "27".into(),
+            "29".into(),
+            "25".into(),
+        ]);
+        let flush_b = calc_hand(&[
+            "2K".into(),
+            "23".into(),
+            "24".into(),
+            "26".into(),
+            "28".into(),
+        ]);
+        assert!(flush_a > flush_b);
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let cards: [Card; 5] = [
+            "21".into(),
+            "36".into(),
+            "47".into(),
+            "18".into(),
+            "29".into(),
+        ];
+        assert_eq!(calc_hand(&cards).hand, HandType::HighCard);
+        let low = calc_hand_with_variant(&cards, Variant::ShortDeck);
+        assert_eq!(low.hand, HandType::Straight);
+        let ten_high = calc_hand_with_variant(
+            &[
+                "26".into(),
+                "37".into(),
+                "48".into(),
+                "19".into(),
+                "2T".into(),
+            ],
+            Variant::ShortDeck,
+        );
+        assert!(low < ten_high);
+
+        let flush: [Card; 5] = [
+            "16".into(),
+            "18".into(),
+            "19".into(),
+            "1J".into(),
+            "1K".into(),
+        ];
+        let full_house: [Card; 5] = [
+            "2A".into(),
+            "3A".into(),
+            "4A".into(),
+            "1K".into(),
+            "2K".into(),
+        ];
+        assert!(calc_hand(&flush) < calc_hand(&full_house));
+        assert!(
+            calc_hand_with_variant(&flush, Variant::ShortDeck)
+                > calc_hand_with_variant(&full_house, Variant::ShortDeck)
+        );
+        assert_eq!(
+            Variant::ShortDeck.cmp_hand_type(HandType::Flush, HandType::FullHouse),
+            core::cmp::Ordering::Greater
+        );
+        assert_eq!(
+            Variant::ShortDeck.cmp_hand_type(HandType::FourOfAKind, HandType::Flush),
+            core::cmp::Ordering::Greater
+        );
+        assert_eq!(iter_all_cards_with_variant(Variant::ShortDeck).count(), 36);
+    }
+
     #[test]
     fn test_iter_all_cards() {
         assert_eq!(iter_all_cards().count(), 52);
@@ -1,5 +1,5 @@
 import { describe, expect, it } from "vitest";
-import { Odds, Stage } from "texas-odds";
+import { OddsJob, Stage } from "texas-odds";
 
 describe("Odds Calculate", () => {
   it("case 5 cards", () => {
@@ -13,4 +13,23 @@ describe("Odds Calculate", () => {
     const odds = stage.odds();
     console.dir(odds);
   });
+
+  it("reports progress", () => {
+    const job = new OddsJob(2, "hA", "dA", "");
+    const done: number[] = [];
+    while (!job.step(1)) {
+      done.push(job.progress);
+    }
+    expect(done.length).toBeGreaterThan(0);
+    expect(job.progress).toBe(1);
+    expect(job.odds().win).toBeGreaterThan(0.8);
+  });
+
+  it("typed results and errors", () => {
+    const odds = new Stage(2, "hA", "dA", "c2 d7 sQ").odds();
+    expect(odds.win + odds.tie).toBeLessThanOrEqual(1);
+    expect(odds.hand_type_rates.Pair).toBeGreaterThan(0);
+    expect(() => new Stage(2, "hA", "x1", "")).toThrow("Invalid card x1");
+    expect(() => new Stage(2, "hA", "hA", "")).toThrow("used twice");
+  });
 });
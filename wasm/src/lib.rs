@@ -1,8 +1,20 @@
 mod utils;
 
-use js_sys::{Object, Reflect};
-use texas_odds::{odds::Odds as OriginOdds, odds::Stage as OriginStage, texas::HandType};
+use std::{cell::RefCell, collections::BTreeMap};
 
+use serde::{Deserialize, Serialize};
+use texas_odds::{
+    cache::{CanonicalKey, OddsCache},
+    odds::get_best_hand,
+    odds::Odds as OriginOdds,
+    odds::OddsJob as OriginOddsJob,
+    odds::Stage as OriginStage,
+    odds::WinRate as OriginWinRate,
+    scenario::Scenario,
+    texas::Card,
+    texas::HandType,
+};
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -11,100 +23,416 @@ use wasm_bindgen::prelude::*;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-#[wasm_bindgen]
-#[derive(Debug, Clone, Copy)]
+thread_local! {
+    /// Odds already computed by `Stage` or `OddsJob`, the page asks for the same spots again and
+    /// again
+    static CACHE: RefCell<OddsCache> = RefCell::new(OddsCache::default());
+}
+
+fn cache_get(key: &CanonicalKey) -> Option<OriginOdds> {
+    CACHE.with(|x| x.borrow_mut().get(key))
+}
+
+fn cache_insert(key: CanonicalKey, odds: OriginOdds) {
+    CACHE.with(|x| x.borrow_mut().insert(key, odds));
+}
+
+/// The rate of each hand type, keyed like `HandType`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(rename_all = "PascalCase")]
+pub struct HandTypeRates {
+    pub high_card: f64,
+    pub pair: f64,
+    pub two_pair: f64,
+    pub three_of_a_kind: f64,
+    pub straight: f64,
+    pub flush: f64,
+    pub full_house: f64,
+    pub four_of_a_kind: f64,
+    pub straight_flush: f64,
+    pub royal_flush: f64,
+}
+
+impl From<&BTreeMap<HandType, f64>> for HandTypeRates {
+    fn from(value: &BTreeMap<HandType, f64>) -> Self {
+        let rate = |hand: HandType| value.get(&hand).copied().unwrap_or(0.0);
+        Self {
+            high_card: rate(HandType::HighCard),
+            pair: rate(HandType::Pair),
+            two_pair: rate(HandType::TwoPair),
+            three_of_a_kind: rate(HandType::ThreeOfAKind),
+            straight: rate(HandType::Straight),
+            flush: rate(HandType::Flush),
+            full_house: rate(HandType::FullHouse),
+            four_of_a_kind: rate(HandType::FourOfAKind),
+            straight_flush: rate(HandType::StraightFlush),
+            royal_flush: rate(HandType::RoyalFlush),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
 pub struct Odds {
-    win: f64,
-    tie: f64,
-    hand_type_rates: [f64; 10],
+    pub win: f64,
+    pub tie: f64,
+    pub hand_type_rates: HandTypeRates,
 }
 
 impl From<OriginOdds> for Odds {
     fn from(value: OriginOdds) -> Self {
-        let mut hand_type_rates = [0.; 10];
-        for (i, hand) in HandType::ALL.iter().enumerate() {
-            let rate = value.hand_rate.get(hand).copied().unwrap_or(0.0);
-            hand_type_rates[i] = rate;
-        }
-
         Self {
             win: value.win,
             tie: value.tie,
-            hand_type_rates,
+            hand_type_rates: (&value.hand_rate).into(),
+        }
+    }
+}
+
+/// Distribution of the win rate over the runouts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct WinRate {
+    pub mean: f64,
+    pub mean_tie_rate: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentile25: f64,
+    pub median: f64,
+    pub percentile75: f64,
+    pub std: f64,
+    /// Our hand types
+    pub self_rate: HandTypeRates,
+    /// The hand types of a random opponent
+    pub other_rate: HandTypeRates,
+    /// `self_rate - other_rate`
+    pub diff_rate: HandTypeRates,
+}
+
+impl From<OriginWinRate> for WinRate {
+    fn from(value: OriginWinRate) -> Self {
+        Self {
+            mean: value.mean,
+            mean_tie_rate: value.mean_tie_rate,
+            min: value.min,
+            max: value.max,
+            percentile25: value.percentile25,
+            median: value.median,
+            percentile75: value.percentile75,
+            std: value.std,
+            self_rate: (&value.self_rate).into(),
+            other_rate: (&value.other_rate).into(),
+            diff_rate: (&value.diff_rate).into(),
+        }
+    }
+}
+
+/// The best five cards of a hand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct HandEval {
+    #[tsify(type = "keyof HandTypeRates")]
+    pub hand_type: String,
+    /// e.g. `full house, K full of 2`
+    pub description: String,
+    /// The five cards that make the hand
+    pub cards: Vec<String>,
+}
+
+fn parse_cards(cards: &str) -> Result<Vec<Card>, String> {
+    cards
+        .split_whitespace()
+        .map(|x| x.parse::<Card>().map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Checks the input instead of panicking like `Stage::new`
+fn new_scenario(
+    n_players: usize,
+    hole_card_0: &str,
+    hole_card_1: &str,
+    community_card: &str,
+) -> Result<Scenario, String> {
+    let hole_cards = parse_cards(&format!("{hole_card_0} {hole_card_1}"))?;
+    let community_cards = parse_cards(community_card)?;
+    Scenario::new(&hole_cards, &community_cards, n_players, &[]).map_err(|e| e.message)
+}
+
+/// 5 to 7 distinct cards
+fn parse_hand(cards: &str) -> Result<Vec<Card>, String> {
+    let cards = parse_cards(cards)?;
+    if cards.len() < 5 || cards.len() > 7 {
+        return Err(format!("Expected 5 to 7 cards, got {}", cards.len()));
+    }
+    for (i, card) in cards.iter().enumerate() {
+        if cards[..i].contains(card) {
+            return Err(format!("{card} is used twice"));
         }
     }
+    Ok(cards)
+}
+
+fn eval_hand(cards: &str) -> Result<HandEval, String> {
+    let (hand, five) = get_best_hand(&parse_hand(cards)?);
+    Ok(HandEval {
+        hand_type: hand.hand_type().to_string(),
+        description: hand.to_string(),
+        cards: five.iter().map(|x| x.to_string()).collect(),
+    })
+}
+
+fn compare_hands(a: &str, b: &str) -> Result<i32, String> {
+    let (a, _) = get_best_hand(&parse_hand(a)?);
+    let (b, _) = get_best_hand(&parse_hand(b)?);
+    Ok(a.cmp(&b) as i32)
+}
+
+/// The best hand of 5 to 7 cards, e.g. `evaluate("hA dA cK sK h2")`
+#[wasm_bindgen]
+pub fn evaluate(cards: &str) -> Result<HandEval, JsError> {
+    utils::set_panic_hook();
+    eval_hand(cards).map_err(|e| JsError::new(&e))
+}
+
+/// Compares the best hands of two sets of 5 to 7 cards: `1` if `a` wins, `-1` if `b` wins, `0`
+/// for a tie
+#[wasm_bindgen]
+pub fn compare(a: &str, b: &str) -> Result<i32, JsError> {
+    utils::set_panic_hook();
+    compare_hands(a, b).map_err(|e| JsError::new(&e))
 }
 
 #[wasm_bindgen]
 pub struct Stage {
     stage: OriginStage,
     n_players: usize,
+    key: CanonicalKey,
     odds: Option<Odds>,
 }
 
 #[wasm_bindgen]
-impl Odds {
-    #[wasm_bindgen(getter)]
-    pub fn win(&self) -> f64 {
-        self.win
+impl Stage {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        n_players: usize,
+        hole_card_0: &str,
+        hole_card_1: &str,
+        community_card: &str,
+    ) -> Result<Stage, JsError> {
+        utils::set_panic_hook();
+        let scenario = new_scenario(n_players, hole_card_0, hole_card_1, community_card)
+            .map_err(|e| JsError::new(&e))?;
+        Ok(Self {
+            n_players,
+            stage: scenario.stage(),
+            key: CanonicalKey::from(&scenario),
+            odds: None,
+        })
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn tie(&self) -> f64 {
-        self.tie
-    }
-
-    pub fn hand_type_rates(&self) -> Object {
-        let obj = Object::new();
-        for (i, hand) in HandType::ALL.iter().enumerate() {
-            Reflect::set(
-                &obj,
-                &hand.to_string().into(),
-                &JsValue::from_f64(self.hand_type_rates[i]),
-            )
-            .unwrap();
+    pub fn odds(&mut self) -> Odds {
+        if let Some(x) = self.odds {
+            return x;
         }
-        obj
+
+        let odds = cache_get(&self.key).unwrap_or_else(|| {
+            let odds = self.stage.win_rate_with_n_players(self.n_players);
+            cache_insert(self.key.clone(), odds.clone());
+            odds
+        });
+        let odds = odds.into();
+        self.odds = Some(odds);
+        odds
     }
 
-    pub fn to_obj(&self) -> Object {
-        let obj = Object::new();
-        Reflect::set(&obj, &"win".into(), &JsValue::from_f64(self.win)).unwrap();
-        Reflect::set(&obj, &"tie".into(), &JsValue::from_f64(self.tie)).unwrap();
-        Reflect::set(&obj, &"hand_type_rates".into(), &self.hand_type_rates()).unwrap();
-        obj
+    /// The distribution of the win rate against one random hand
+    #[wasm_bindgen(js_name = winRate)]
+    pub fn win_rate(&self) -> WinRate {
+        self.stage.win_rate().into()
     }
 }
 
+/// Hands evaluated between two looks at the clock
+const STEP_HANDS: usize = 1024;
+
+/// Steps `job` until it is done or `budget_ms` have passed on the `now` clock
+fn run_for(job: &mut OriginOddsJob, budget_ms: f64, now: impl Fn() -> f64) -> bool {
+    let start = now();
+    loop {
+        if job.step(STEP_HANDS) {
+            return true;
+        }
+        if now() - start >= budget_ms {
+            return false;
+        }
+    }
+}
+
+/// The odds of a stage computed a little at a time, so it can run in an idle loop or a Web Worker
+/// without freezing the page.
+///
+/// ```js
+/// const job = new OddsJob(2, "hA", "dA", "");
+/// while (!job.step(16)) {
+///   console.log(job.progress, job.odds());
+///   await new Promise((r) => setTimeout(r));
+/// }
+/// ```
 #[wasm_bindgen]
-impl Stage {
+pub struct OddsJob {
+    key: CanonicalKey,
+    state: JobState,
+}
+
+enum JobState {
+    Running(OriginOddsJob),
+    /// Found in the cache or finished
+    Done(OriginOdds),
+}
+
+#[wasm_bindgen]
+impl OddsJob {
     #[wasm_bindgen(constructor)]
     pub fn new(
         n_players: usize,
         hole_card_0: &str,
         hole_card_1: &str,
         community_card: &str,
-    ) -> Self {
-        Self {
-            n_players,
-            stage: OriginStage::new(
-                [hole_card_0.into(), hole_card_1.into()],
-                &community_card
-                    .split_whitespace()
-                    .map(|x| x.into())
-                    .collect::<Vec<_>>(),
-            ),
-            odds: None,
+    ) -> Result<OddsJob, JsError> {
+        utils::set_panic_hook();
+        let scenario = new_scenario(n_players, hole_card_0, hole_card_1, community_card)
+            .map_err(|e| JsError::new(&e))?;
+        let key = CanonicalKey::from(&scenario);
+        let state = match cache_get(&key) {
+            Some(odds) => JobState::Done(odds),
+            None => JobState::Running(OriginOddsJob::new(&scenario.stage(), n_players)),
+        };
+        Ok(Self { key, state })
+    }
+
+    /// Runs for about `budget_ms` milliseconds, returns whether the job is done
+    pub fn step(&mut self, budget_ms: f64) -> bool {
+        self.step_with_clock(budget_ms, js_sys::Date::now)
+    }
+
+    /// Fraction of the work done
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        match &self.state {
+            JobState::Running(job) => job.progress(),
+            JobState::Done(_) => 1.,
         }
     }
 
-    pub fn odds(&mut self) -> Object {
-        if let Some(x) = self.odds {
-            return x.to_obj();
+    #[wasm_bindgen(getter)]
+    pub fn done(&self) -> bool {
+        matches!(self.state, JobState::Done(_))
+    }
+
+    /// The odds so far, exact once the job is done
+    pub fn odds(&self) -> Odds {
+        match &self.state {
+            JobState::Running(job) => job.odds().into(),
+            JobState::Done(odds) => odds.clone().into(),
         }
+    }
+}
+
+impl OddsJob {
+    fn step_with_clock(&mut self, budget_ms: f64, now: impl Fn() -> f64) -> bool {
+        let JobState::Running(job) = &mut self.state else {
+            return true;
+        };
+        if !run_for(job, budget_ms, now) {
+            return false;
+        }
+        let odds = job.odds();
+        cache_insert(self.key.clone(), odds.clone());
+        self.state = JobState::Done(odds);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        compare_hands, eval_hand, new_scenario, run_for, OddsJob, OriginOddsJob, Stage, WinRate,
+    };
+    use std::cell::Cell;
+
+    #[test]
+    fn budget() {
+        let stage = new_scenario(3, "hA", "dA", "c2 d7 sQ").unwrap().stage();
+        let mut job = OriginOddsJob::new(&stage, 3);
+        // a fake clock that moves 1ms each time it is read
+        let clock = Cell::new(0.);
+        let now = || {
+            clock.set(clock.get() + 1.);
+            clock.get()
+        };
+        let mut steps = 0;
+        while !run_for(&mut job, 4., now) {
+            steps += 1;
+            assert!(job.progress() < 1.);
+        }
+        assert!(steps > 1);
+        assert!(job.is_done());
+        let exact = stage.win_rate_with_n_players(3);
+        assert!((job.odds().win - exact.win).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cache() {
+        let mut stage = Stage::new(2, "hA", "dA", "c2 d7 sQ s3 h9").unwrap();
+        let odds = stage.odds();
+        // the same spot with other suits
+        let job = OddsJob::new(2, "sA", "cA", "h2 c7 dQ d3 s9").unwrap();
+        assert!(job.done());
+        assert_eq!(job.progress(), 1.);
+        assert_eq!(job.odds(), odds);
+
+        let mut job = OddsJob::new(3, "hA", "dA", "c2 d7 sQ s3 h8").unwrap();
+        assert!(!job.done());
+        assert!(job.step_with_clock(f64::INFINITY, || 0.));
+        let mut stage = Stage::new(3, "dA", "hA", "d2 h7 cQ c3 s8").unwrap();
+        assert_eq!(stage.odds(), job.odds());
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(
+            new_scenario(2, "hA", "x1", "").err().unwrap(),
+            "Invalid card x1"
+        );
+        assert!(new_scenario(2, "hA", "dA", "c2 d7").is_err());
+        assert!(new_scenario(2, "hA", "dA", "hA d7 s9").is_err());
+        assert!(new_scenario(1, "hA", "dA", "").is_err());
+    }
+
+    #[test]
+    fn eval_and_compare() {
+        let eval = eval_hand("hA dA cK sK h2 d2 c9").unwrap();
+        assert_eq!(eval.hand_type, "TwoPair");
+        assert_eq!(eval.description, "two pair A and K, kicker 9");
+        assert_eq!(eval.cards.len(), 5);
+        assert!(eval_hand("hA dA cK").is_err());
+        assert_eq!(
+            eval_hand("hA dA cK sK hA").err().unwrap(),
+            "♥️A is used twice"
+        );
+
+        assert_eq!(compare_hands("hA dA cK sK h2", "hQ dQ cK sK h2"), Ok(1));
+        assert_eq!(compare_hands("h3 d4 c5 s6 h7", "d3 c4 s5 h6 d7"), Ok(0));
+        assert_eq!(compare_hands("h3 d4 c5 s9 h7", "hA dA cK sK hQ"), Ok(-1));
+    }
 
-        let odds = self.stage.win_rate_with_n_players(self.n_players);
-        self.odds = Some(odds.into());
-        self.odds.unwrap().to_obj()
+    #[test]
+    fn win_rate() {
+        let stage = new_scenario(2, "hA", "dA", "c2 d7 sQ").unwrap().stage();
+        let win_rate = WinRate::from(stage.win_rate());
+        assert!(win_rate.min <= win_rate.median && win_rate.median <= win_rate.max);
+        assert!(win_rate.self_rate.pair > 0.);
+        let diff = win_rate.self_rate.pair - win_rate.other_rate.pair;
+        assert!((win_rate.diff_rate.pair - diff).abs() < 1e-9);
     }
 }
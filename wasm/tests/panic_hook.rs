@@ -0,0 +1,19 @@
+//! A panic after a `Stage` is created goes through the console panic hook, so it is logged in
+//! the browser. The only test of this binary, the hook is installed once per process.
+#![cfg(feature = "console_error_panic_hook")]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use wasm::Stage;
+
+static DEFAULT_HOOK_CALLED: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn stage_installs_the_panic_hook() {
+    std::panic::set_hook(Box::new(|_| {
+        DEFAULT_HOOK_CALLED.store(true, Ordering::SeqCst)
+    }));
+    let _stage = Stage::new(2, "hA", "dA", "");
+    let _ = std::panic::catch_unwind(|| panic!("after Stage::new"));
+    assert!(!DEFAULT_HOOK_CALLED.load(Ordering::SeqCst));
+}
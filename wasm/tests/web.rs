@@ -3,6 +3,7 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use wasm::OddsJob;
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -11,3 +12,13 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn odds_job() {
+    let mut job = OddsJob::new(2, "hA", "dA", "c2 d7 sQ").unwrap();
+    while !job.step(1.) {
+        assert!(job.progress() < 1.);
+    }
+    assert!(job.done());
+    assert_eq!(job.progress(), 1.);
+}